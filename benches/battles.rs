@@ -0,0 +1,142 @@
+/*
+    Interpreter throughput on a few classic pairings, under the '94 hill
+    rules. Every pairing is measured twice over the same seeded rounds:
+    once counting executed instructions (cycles/s) and once counting
+    battles (battles/s). Rounds are loaded into one reused runtime with
+    `brawl_into` outside the timing, so only the ticks are measured. A last
+    group plays whole matches through an
+    `Arena`, placement included, the way evolution and the hill do.
+
+        cargo bench --bench battles
+*/
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use rand::{rngs::StdRng, SeedableRng};
+
+use core_war_vm::{
+    battle::{Arena, BattleConfig},
+    core::{CoreConfig, CoreRuntime},
+    warrior::Warrior,
+};
+
+const ROUNDS: usize = 8;
+
+/// Warriors by their path under src/test/warriors.
+const PAIRINGS: [(&str, &str); 3] = [
+    ("dwarf", "imp"),
+    ("classic/chang", "classic/mice"),
+    ("split/paper", "classic/stone"),
+];
+
+fn load(path: &str, core_size: usize) -> Warrior {
+    Warrior::load(&format!("src/test/warriors/{path}.red"), core_size).unwrap()
+}
+
+/// The pairing as benchmarks are named, "paper_vs_stone".
+fn label(a: &str, b: &str) -> String {
+    let name = |path: &str| path.rsplit('/').next().unwrap().to_string();
+    format!("{}_vs_{}", name(a), name(b))
+}
+
+/// Plays the round loaded in `runtime` to the end, returning how many
+/// instructions it took.
+fn battle(runtime: &mut CoreRuntime, config: &BattleConfig) -> usize {
+    let max_ticks = config.max_cycles * 2;
+
+    for tick in 0..max_ticks {
+        if runtime.warriors.len() <= 1 {
+            return tick;
+        }
+
+        runtime.tick();
+    }
+
+    max_ticks
+}
+
+fn battles(c: &mut Criterion) {
+    let config = BattleConfig::preset("94nop").unwrap();
+
+    for (a, b) in PAIRINGS {
+        let warriors = [load(a, config.core_size), load(b, config.core_size)];
+
+        // the same placements for every iteration, so the work is fixed
+        let mut rng = StdRng::seed_from_u64(0);
+        let rounds: Vec<CoreConfig> = (0..ROUNDS)
+            .map(|_| {
+                let mut core_conf = config.core_config();
+                for warrior in warriors.iter() {
+                    core_conf
+                        .deploy_with_rng(warrior.clone(), None, &mut rng)
+                        .unwrap();
+                }
+                core_conf
+            })
+            .collect();
+
+        let mut runtime = rounds[0].brawl();
+        let cycles: usize = rounds
+            .iter()
+            .map(|round| {
+                round.brawl_into(&mut runtime);
+                battle(&mut runtime, &config)
+            })
+            .sum();
+
+        // the time spent ticking through every round, `iters` times over
+        let mut run = |iters: u64| {
+            let mut elapsed = Duration::ZERO;
+            for _ in 0..iters {
+                for round in rounds.iter() {
+                    round.brawl_into(&mut runtime);
+                    let start = Instant::now();
+                    black_box(battle(&mut runtime, &config));
+                    elapsed += start.elapsed();
+                }
+            }
+            elapsed
+        };
+
+        let mut group = c.benchmark_group(label(a, b));
+        group.sample_size(10);
+
+        group.throughput(Throughput::Elements(cycles as u64));
+        group.bench_function("cycles", |bench| bench.iter_custom(&mut run));
+
+        group.throughput(Throughput::Elements(ROUNDS as u64));
+        group.bench_function("battles", |bench| bench.iter_custom(&mut run));
+
+        group.finish();
+    }
+}
+
+fn matches(c: &mut Criterion) {
+    let mut config = BattleConfig::preset("94nop").unwrap();
+    config.rounds = ROUNDS;
+    let mut group = c.benchmark_group("matches");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(ROUNDS as u64));
+
+    for (a, b) in PAIRINGS {
+        let warriors = [load(a, config.core_size), load(b, config.core_size)];
+        let warriors: Vec<&Warrior> = warriors.iter().collect();
+        let mut arena = Arena::new(&config);
+
+        group.bench_function(label(a, b), |bench| {
+            bench.iter(|| {
+                let mut rng = StdRng::seed_from_u64(0);
+                arena.run_match(&warriors, &config, &mut rng).unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, battles, matches);
+criterion_main!(benches);
@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+
+use crate::instruction::op_code::OpCode;
+
+/// Redcode source with its labels, EQU constants and expressions resolved,
+/// what's left is one instruction per line with plain numbers as operands.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assembled {
+    /// Each instruction with the (0 based) source line it came from.
+    pub lines: Vec<(usize, String)>,
+    pub org: Option<usize>,
+}
+
+/// Nested EQUs deeper than this are taken to refer to themselves.
+const MAX_DEPTH: usize = 64;
+
+struct Symbols {
+    labels: HashMap<String, usize>,
+    equs: HashMap<String, String>,
+}
+
+impl Symbols {
+    /// Evaluates `expr` as seen from instruction `at`, where a label is its
+    /// distance from there.
+    fn evaluate(&self, expr: &str, at: usize, depth: usize) -> Result<isize, String> {
+        if depth > MAX_DEPTH {
+            return Err(format!("\"{expr}\" nests too deep, is an EQU circular?"));
+        }
+
+        evaluate_with(expr, &mut |name| {
+            if let Some(label) = self.labels.get(name) {
+                Ok(*label as isize - at as isize)
+            } else if let Some(text) = self.equs.get(name) {
+                self.evaluate(text, at, depth + 1)
+            } else {
+                Err(format!("unknown symbol {name}"))
+            }
+        })
+    }
+}
+
+fn is_opcode(token: &str) -> bool {
+    let code = token.split('.').next().unwrap_or("");
+    OpCode::ALL.iter().any(|op| op.to_string() == code)
+}
+
+fn is_label(token: &str) -> bool {
+    token.starts_with(|c: char| c.is_alphabetic() || c == '_')
+        && token.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn is_pseudo(token: &str) -> bool {
+    matches!(token, "ORG" | "END" | "EQU")
+}
+
+/// Resolves labels, `EQU`s and operand expressions, and the start given by
+/// `ORG` or `END`. Expects source already in upper case.
+pub fn assemble(source: &str) -> Result<Assembled, String> {
+    let mut symbols = Symbols {
+        labels: HashMap::new(),
+        equs: HashMap::new(),
+    };
+    // instruction index, source line, opcode and operands
+    let mut instructions: Vec<(usize, String, String)> = vec![];
+    // ORG or END operand and the line it's on
+    let mut start: Option<(usize, String)> = None;
+    let mut org_found = false;
+
+    for (i, line) in source.split('\n').enumerate() {
+        let line = line.split(';').next().unwrap_or("").trim();
+        let mut rest = line;
+        let mut labels = vec![];
+
+        while let Some(token) = rest.split_whitespace().next() {
+            if is_opcode(token) || is_pseudo(token) || !is_label(token.trim_end_matches(':')) {
+                break;
+            }
+            labels.push(token.trim_end_matches(':').to_string());
+            rest = rest[token.len()..].trim_start();
+        }
+
+        let token = rest.split_whitespace().next().unwrap_or("");
+        let operands = rest[token.len()..].trim().to_string();
+
+        match token {
+            "EQU" => {
+                let [name] = &labels[..] else {
+                    return Err(format!("linea {i}: EQU needs exactly one name"));
+                };
+                symbols.equs.insert(name.clone(), operands);
+                continue;
+            }
+            "END" => {
+                if !operands.is_empty() && !org_found {
+                    start = Some((i, operands));
+                }
+                for label in labels {
+                    symbols.labels.insert(label, instructions.len());
+                }
+                break;
+            }
+            _ => (),
+        }
+
+        for label in labels {
+            if symbols.labels.contains_key(&label) || symbols.equs.contains_key(&label) {
+                return Err(format!("linea {i}: {label} is defined twice"));
+            }
+            symbols.labels.insert(label, instructions.len());
+        }
+
+        match token {
+            "" => (),
+            "ORG" => {
+                if org_found {
+                    return Err(format!("linea {i}: multiple ORG pseudoinstructions found"));
+                }
+                org_found = true;
+                // a bare ORG starts at the next instruction
+                let operand = match operands.is_empty() {
+                    true => instructions.len().to_string(),
+                    false => operands,
+                };
+                start = Some((i, operand));
+            }
+            _ => instructions.push((i, token.to_string(), operands)),
+        }
+    }
+
+    let org = match start {
+        Some((i, operand)) => {
+            let org = symbols
+                .evaluate(&operand, 0, 0)
+                .map_err(|err| format!("linea {i}: invalid ORG operand {operand}: {err}"))?;
+            Some(
+                usize::try_from(org)
+                    .map_err(|_| format!("linea {i}: invalid ORG operand {operand}"))?,
+            )
+        }
+        None => None,
+    };
+
+    let lines = instructions
+        .iter()
+        .enumerate()
+        .map(|(at, (i, code, operands))| {
+            let operands = operands
+                .split(',')
+                .map(str::trim)
+                .filter(|operand| !operand.is_empty())
+                .map(|operand| {
+                    let mode = operand
+                        .chars()
+                        .next()
+                        .filter(|c| "#$@*<>{}".contains(*c))
+                        .map_or("".into(), String::from);
+                    let value = symbols.evaluate(&operand[mode.len()..], at, 0)?;
+                    Ok(format!("{mode}{value}"))
+                })
+                .collect::<Result<Vec<_>, String>>()
+                .map_err(|err| format!("linea {i}: {err}"))?;
+
+            Ok((*i, format!("{code} {}", operands.join(", "))))
+        })
+        .collect::<Result<_, String>>()?;
+
+    Ok(Assembled { lines, org })
+}
+
+/// Evaluates an integer expression with `+ - * / %`, parentheses and names
+/// resolved by `symbol`. Division truncates, like in C.
+pub fn evaluate_with(
+    expr: &str,
+    symbol: &mut impl FnMut(&str) -> Result<isize, String>,
+) -> Result<isize, String> {
+    let tokens = tokenize(expr)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        pos: 0,
+        symbol,
+    };
+    let value = parser.sum()?;
+    match parser.tokens.get(parser.pos) {
+        None => Ok(value),
+        Some(token) => Err(format!("unexpected {token:?} in \"{expr}\"")),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(isize),
+    Name(String),
+    Op(char),
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = expr.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() {
+            let mut number = String::new();
+            while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                number.push(d);
+                chars.next();
+            }
+            let number = number
+                .parse()
+                .map_err(|_| format!("number {number} is too large"))?;
+            tokens.push(Token::Number(number));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut name = String::new();
+            while let Some(&d) = chars.peek().filter(|d| d.is_alphanumeric() || **d == '_') {
+                name.push(d);
+                chars.next();
+            }
+            tokens.push(Token::Name(name));
+        } else if "+-*/%()".contains(c) {
+            tokens.push(Token::Op(c));
+            chars.next();
+        } else {
+            return Err(format!("unexpected '{c}' in \"{expr}\""));
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a, F> {
+    tokens: &'a [Token],
+    pos: usize,
+    symbol: &'a mut F,
+}
+
+impl<F: FnMut(&str) -> Result<isize, String>> Parser<'_, F> {
+    fn eat(&mut self, ops: &str) -> Option<char> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) if ops.contains(*op) => {
+                self.pos += 1;
+                Some(*op)
+            }
+            _ => None,
+        }
+    }
+
+    fn sum(&mut self) -> Result<isize, String> {
+        let mut value = self.product()?;
+        while let Some(op) = self.eat("+-") {
+            let rhs = self.product()?;
+            value = match op {
+                '+' => value.wrapping_add(rhs),
+                _ => value.wrapping_sub(rhs),
+            };
+        }
+        Ok(value)
+    }
+
+    fn product(&mut self) -> Result<isize, String> {
+        let mut value = self.unary()?;
+        while let Some(op) = self.eat("*/%") {
+            let rhs = self.unary()?;
+            value = match op {
+                '*' => value.wrapping_mul(rhs),
+                _ if rhs == 0 => return Err("division by zero".into()),
+                '/' => value / rhs,
+                _ => value % rhs,
+            };
+        }
+        Ok(value)
+    }
+
+    fn unary(&mut self) -> Result<isize, String> {
+        match self.eat("+-") {
+            Some('-') => Ok(-self.unary()?),
+            Some(_) => self.unary(),
+            None => self.atom(),
+        }
+    }
+
+    fn atom(&mut self) -> Result<isize, String> {
+        if self.eat("(").is_some() {
+            let value = self.sum()?;
+            return match self.eat(")") {
+                Some(_) => Ok(value),
+                None => Err("missing )".into()),
+            };
+        }
+
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Number(n)) => Ok(n),
+            Some(Token::Name(name)) => (self.symbol)(&name),
+            Some(token) => Err(format!("unexpected {token:?}")),
+            None => Err("expression ends early".into()),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    battle::{Arena, BattleConfig, MatchResult},
+    warrior::Warrior,
+};
+
+/// Evaluates many matches over a pool of threads. Every thread keeps a
+/// single core around and reuses it for every match it plays.
+pub struct Batch {
+    pub config: BattleConfig,
+    pub threads: usize,
+    /// Seeds the placement of every match. The same seed gives the same
+    /// results no matter how many threads play them.
+    pub seed: Option<u64>,
+    /// Collects `WarriorStats` into every result.
+    pub stats: bool,
+}
+
+impl Batch {
+    pub fn new(config: BattleConfig) -> Self {
+        Self {
+            config,
+            threads: thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            seed: None,
+            stats: false,
+        }
+    }
+
+    /// Plays every matchup, each a list of indices into `warriors`, and
+    /// returns their results in the same order.
+    pub fn run(
+        &self,
+        warriors: &[Warrior],
+        matchups: &[Vec<usize>],
+    ) -> Result<Vec<MatchResult>, String> {
+        if let Some(i) = matchups.iter().flatten().find(|i| **i >= warriors.len()) {
+            return Err(format!(
+                "matchup refers to warrior {i}, only {} given",
+                warriors.len()
+            ));
+        }
+
+        let seed = self.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        let next = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<Result<MatchResult, String>>>> =
+            Mutex::new(vec![None; matchups.len()]);
+
+        thread::scope(|scope| {
+            for _ in 0..self.threads.max(1).min(matchups.len()) {
+                scope.spawn(|| {
+                    let mut arena = Arena::new(&self.config);
+                    arena.collect_stats(self.stats);
+                    let mut fighters: Vec<&Warrior> = vec![];
+
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(matchup) = matchups.get(i) else {
+                            break;
+                        };
+
+                        // seeded by position, so which thread plays it doesn't matter
+                        let mut rng = StdRng::seed_from_u64(
+                            seed.wrapping_add((i as u64).wrapping_mul(0x9E3779B97F4A7C15)),
+                        );
+
+                        fighters.clear();
+                        fighters.extend(matchup.iter().map(|w| &warriors[*w]));
+                        let result = arena.run_match(&fighters, &self.config, &mut rng);
+
+                        results.lock().unwrap()[i] = Some(result);
+                    }
+                });
+            }
+        });
+
+        results
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|result| result.expect("every matchup is played"))
+            .collect()
+    }
+}
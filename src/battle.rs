@@ -0,0 +1,207 @@
+use rand::Rng;
+
+use crate::{
+    core::{stats::WarriorStats, CoreConfig, CoreRuntime},
+    warrior::Warrior,
+};
+
+/// Rules a match is played under, in the terms pMARS uses for them.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BattleConfig {
+    pub core_size: usize,
+    /// A cycle lets every warrior still alive execute one instruction.
+    pub max_cycles: usize,
+    pub max_processes: usize,
+    pub max_length: usize,
+    pub min_distance: usize,
+    pub rounds: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatchResult {
+    pub wins: Vec<usize>,
+    pub losses: Vec<usize>,
+    pub ties: Vec<usize>,
+    pub scores: Vec<f64>,
+    /// Summed over every round, only if the arena collects them.
+    pub stats: Vec<WarriorStats>,
+}
+
+impl BattleConfig {
+    /// The '94 standard hill settings scaled to `core_size`.
+    pub fn new(core_size: usize) -> Self {
+        Self {
+            core_size,
+            max_cycles: core_size * 10,
+            max_processes: core_size,
+            max_length: (core_size / 80).max(1),
+            min_distance: (core_size / 80).max(1),
+            rounds: 1,
+        }
+    }
+
+    /// Settings of the usual King of the Hill standards.
+    pub fn preset(name: &str) -> Option<Self> {
+        let (core_size, max_cycles, max_processes, max_length) = match name {
+            "94nop" => (8000, 80000, 8000, 100),
+            "94x" => (55440, 500000, 10000, 200),
+            "lp" => (8000, 80000, 8, 200),
+            "tiny" => (800, 8000, 800, 20),
+            "nano" => (80, 800, 80, 5),
+            _ => return None,
+        };
+
+        Some(Self {
+            core_size,
+            max_cycles,
+            max_processes,
+            max_length,
+            min_distance: max_length,
+            rounds: 250,
+        })
+    }
+
+    pub fn core_config(&self) -> CoreConfig {
+        let mut core_conf = CoreConfig::new(self.core_size);
+        core_conf.set_max_processes(self.max_processes);
+        core_conf.set_min_distance(self.min_distance);
+        core_conf
+    }
+
+    /// Points a survivor earns in one round, shared among `survivors`. Two
+    /// warrior matches use the usual 3 per win and 1 per tie, melees use the
+    /// pMARS (W*W-1)/S formula.
+    pub fn round_points(&self, warriors: usize, survivors: usize) -> f64 {
+        if warriors == 2 {
+            if survivors == 1 {
+                3.
+            } else {
+                1.
+            }
+        } else {
+            (warriors * warriors - 1) as f64 / survivors as f64
+        }
+    }
+}
+
+/// A core buffer battles can be played in one after another without
+/// allocating a new core for each of them.
+pub struct Arena {
+    runtime: CoreRuntime,
+}
+
+impl Arena {
+    pub fn new(config: &BattleConfig) -> Self {
+        Self {
+            runtime: CoreConfig::new(config.core_size).brawl(),
+        }
+    }
+
+    /// Starts or stops collecting `WarriorStats` for the rounds played.
+    pub fn collect_stats(&mut self, on: bool) {
+        self.runtime.collect_stats(on);
+    }
+
+    /// Plays a single round with random placement, returning which of the
+    /// `warriors` are still alive once it ends.
+    pub fn fight(
+        &mut self,
+        warriors: &[&Warrior],
+        config: &BattleConfig,
+        rng: &mut impl Rng,
+    ) -> Result<Vec<bool>, String> {
+        for warrior in warriors {
+            if warrior.body.len() > config.max_length {
+                return Err(format!(
+                    "{} is {} instructions long, the limit is {}",
+                    warrior.name,
+                    warrior.body.len(),
+                    config.max_length
+                ));
+            }
+        }
+
+        let runtime = &mut self.runtime;
+        runtime.core_size = config.core_size;
+        runtime.load_with_rng(warriors, config.max_processes, config.min_distance, rng)?;
+
+        // a lone warrior plays until it dies, otherwise the last one standing wins
+        let last_standing = if warriors.len() > 1 { 1 } else { 0 };
+
+        for _ in 0..config.max_cycles * warriors.len() {
+            if runtime.warriors.len() <= last_standing {
+                break;
+            }
+
+            runtime.tick();
+        }
+
+        let mut alive = vec![false; warriors.len()];
+        for warrior in runtime.warriors.iter().filter(|w| !w.dead()) {
+            alive[warrior.deploy_index] = true;
+        }
+
+        Ok(alive)
+    }
+
+    /// Plays `config.rounds` rounds between `warriors`, tallying wins, losses,
+    /// ties and score for each of them in the order they were given.
+    pub fn run_match(
+        &mut self,
+        warriors: &[&Warrior],
+        config: &BattleConfig,
+        rng: &mut impl Rng,
+    ) -> Result<MatchResult, String> {
+        let n = warriors.len();
+        let mut result = MatchResult {
+            wins: vec![0; n],
+            losses: vec![0; n],
+            ties: vec![0; n],
+            scores: vec![0.; n],
+            stats: vec![],
+        };
+
+        for _ in 0..config.rounds {
+            let alive = self.fight(warriors, config, rng)?;
+            let survivors = alive.iter().filter(|a| **a).count();
+
+            if let Some(stats) = self.runtime.stats() {
+                result.stats.resize(n, WarriorStats::default());
+                for (total, round) in result.stats.iter_mut().zip(stats) {
+                    total.add(round);
+                }
+            }
+
+            for (i, alive) in alive.into_iter().enumerate() {
+                if !alive {
+                    result.losses[i] += 1;
+                    continue;
+                }
+
+                if survivors == 1 {
+                    result.wins[i] += 1;
+                } else {
+                    result.ties[i] += 1;
+                }
+
+                result.scores[i] += config.round_points(n, survivors);
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// Plays a single round in a fresh core, see `Arena::fight`.
+pub fn fight(warriors: &[Warrior], config: &BattleConfig) -> Result<Vec<bool>, String> {
+    let warriors: Vec<&Warrior> = warriors.iter().collect();
+    Arena::new(config).fight(&warriors, config, &mut rand::thread_rng())
+}
+
+/// Plays a whole match in a fresh core, see `Arena::run_match`.
+pub fn run_match(warriors: &[Warrior], config: &BattleConfig) -> Result<MatchResult, String> {
+    let warriors: Vec<&Warrior> = warriors.iter().collect();
+    Arena::new(config).run_match(&warriors, config, &mut rand::thread_rng())
+}
@@ -0,0 +1,82 @@
+use std::fs;
+
+use crate::{batch::Batch, battle::BattleConfig, warrior::Warrior};
+
+/// A fixed set of warriors others are measured against, such as the Wilkies
+/// or WilFiz sets.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Benchmark {
+    pub warriors: Vec<Warrior>,
+    /// Rounds played against every benchmark warrior, with random placement.
+    pub rounds: usize,
+}
+
+impl Benchmark {
+    pub fn new(warriors: Vec<Warrior>) -> Self {
+        Self {
+            warriors,
+            rounds: 100,
+        }
+    }
+
+    /// Builds a benchmark out of every `.red` file in `dir`.
+    pub fn load_dir(dir: &str, core_size: usize) -> Result<Self, String> {
+        let mut paths: Vec<String> = fs::read_dir(dir)
+            .map_err(|err| format!("reading {dir} failed: {err}"))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "red"))
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+
+        paths.sort();
+
+        let warriors = paths
+            .iter()
+            .map(|path| Warrior::load(path, core_size))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if warriors.is_empty() {
+            return Err(format!("no .red warriors in {dir}"));
+        }
+
+        Ok(Self::new(warriors))
+    }
+
+    /// The benchmark score of each of `warriors`: the points it earns per
+    /// round against a benchmark warrior, 3 for a win and 1 for a tie, times
+    /// 100 and averaged over the whole set. So 300 means every round was
+    /// won and 100 is as good as tying them all.
+    pub fn score(&self, warriors: &[Warrior], batch: &Batch) -> Result<Vec<f64>, String> {
+        if self.warriors.is_empty() || self.rounds == 0 {
+            return Err("a benchmark needs at least one warrior and one round".into());
+        }
+
+        let n = warriors.len();
+        let mut pool = warriors.to_vec();
+        pool.extend(self.warriors.iter().cloned());
+
+        let matchups: Vec<Vec<usize>> = (0..n)
+            .flat_map(|w| (0..self.warriors.len()).map(move |b| vec![w, n + b]))
+            .collect();
+
+        let batch = Batch {
+            config: BattleConfig {
+                rounds: self.rounds,
+                ..batch.config.clone()
+            },
+            threads: batch.threads,
+            seed: batch.seed,
+            stats: batch.stats,
+        };
+
+        let mut scores = vec![0.; n];
+        for (matchup, result) in matchups.iter().zip(batch.run(&pool, &matchups)?) {
+            scores[matchup[0]] += result.scores[0];
+        }
+
+        let rounds = (self.rounds * self.warriors.len()) as f64;
+        Ok(scores.into_iter().map(|s| s * 100. / rounds).collect())
+    }
+}
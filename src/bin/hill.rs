@@ -0,0 +1,51 @@
+use std::{path::Path, time::Duration};
+
+use core_war_vm::{
+    environment::Environment,
+    hill::{Hill, HILL_SIZE},
+};
+
+const USAGE: &str =
+    "usage: hill <hill dir> [--env 94nop|94x|tiny|nano|lp|<file>.toml] [--size N] (--spool <dir> [--poll <secs>] | --listen <addr>)";
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let dir = args.first().ok_or(USAGE)?;
+    let mut env = "94nop".to_string();
+    let mut size = HILL_SIZE;
+    let mut spool = None;
+    let mut poll = 5;
+    let mut listen = None;
+
+    let mut rest = args[1..].iter();
+    while let Some(flag) = rest.next() {
+        let value = rest.next().ok_or(USAGE)?;
+        let number = || value.parse::<usize>().map_err(|_| USAGE.to_string());
+
+        match flag.as_str() {
+            "--env" => env = value.clone(),
+            "--size" => size = number()?,
+            "--spool" => spool = Some(value.clone()),
+            "--poll" => poll = number()?,
+            "--listen" => listen = Some(value.clone()),
+            _ => return Err(USAGE.into()),
+        }
+    }
+
+    let dir = Path::new(dir);
+    let mut hill = if dir.join("rules").exists() {
+        Hill::open(dir)?
+    } else {
+        let env = Environment::find(&env)?;
+        Hill::create(dir, &env.name, env.battle, size)?
+    };
+
+    match (spool, listen) {
+        (Some(spool), None) => {
+            hill.serve_spool(Path::new(&spool), Duration::from_secs(poll as u64))
+        }
+        (None, Some(addr)) => hill.serve_tcp(&addr),
+        _ => Err(USAGE.into()),
+    }
+}
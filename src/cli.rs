@@ -0,0 +1,843 @@
+use std::{
+    fmt, fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    batch::Batch,
+    battle::BattleConfig,
+    benchmark::Benchmark,
+    crossover::CrossoverOperator,
+    environment::Environment,
+    evolution::{Evolution, EvolutionConfig, GenerationReport, Selection},
+    hall_of_fame::{HallOfFameConfig, Sampling},
+    islands::{Islands, IslandsConfig, Topology},
+    mutation::MutationOperator,
+    settings::{self, Table},
+    tournament::{json_string, Tournament},
+    warrior::Warrior,
+};
+
+pub const USAGE: &str = "usage: core_war_vm <command> [options]
+
+commands:
+  run [--env ENV] [--benchmark DIR] [--checkpoint DIR] [--resume] [--log DIR] [--ciao]
+      the island experiment, on 8000 cells if ENV isn't given
+  battle A.red B.red... [RULES] [--seed N] [--json]
+      plays a match, pMARS style
+  tournament DIR [RULES] [--melee N] [--seed N] [--format text|csv|json]
+      plays every warrior in DIR against every other one
+  evolve --config FILE [--resume] [--json]
+      evolves warriors as FILE describes
+  asm FILE [-s N] [--env ENV]
+      prints the load file FILE assembles to
+  debug A.red B.red... [RULES] [--seed N]
+      steps through a battle, commands read from stdin
+
+RULES, with the pMARS defaults:
+  --env ENV      start from ENV instead, a preset (94nop, 94x, tiny, nano,
+                 lp) or an environment .toml file
+  -r rounds (1)  -s core size (8000)  -c cycles (80000)  -p processes (8000)
+  -l max length (100)  -d min distance (100)";
+
+const DEBUG_HELP: &str = "s [N]        execute N instructions, 1 if not given
+c            continue until the battle is over
+l [A [B]]    list the core from A to B, around the next instruction if not given
+p            show every warrior's process queue
+q            quit";
+
+/// How a command failed, which decides the exit code.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliError {
+    /// Bad arguments, exit code 2.
+    Usage(String),
+    /// Everything else, exit code 1.
+    Failed(String),
+}
+
+impl CliError {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            CliError::Usage(_) => 2,
+            CliError::Failed(_) => 1,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(err) => write!(f, "{err}\n\n{USAGE}"),
+            CliError::Failed(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl From<String> for CliError {
+    fn from(err: String) -> Self {
+        CliError::Failed(err)
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(err: io::Error) -> Self {
+        CliError::Failed(err.to_string())
+    }
+}
+
+fn usage(err: impl Into<String>) -> CliError {
+    CliError::Usage(err.into())
+}
+
+/// Runs the command `args` (without the program name) names, reading from
+/// `input` and writing to `out`.
+pub fn main(
+    args: &[String],
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> Result<(), CliError> {
+    let (command, args) = args
+        .split_first()
+        .ok_or_else(|| usage("no command given"))?;
+
+    match command.as_str() {
+        "run" => run(args, out),
+        "battle" => battle(args, out),
+        "tournament" => tournament(args, out),
+        "evolve" => evolve(args, out),
+        "asm" => asm(args, out),
+        "debug" => debug(args, input, out),
+        "help" | "-h" | "--help" => Ok(writeln!(out, "{USAGE}")?),
+        _ => Err(usage(format!("unknown command {command}"))),
+    }
+}
+
+/// Positional arguments and options, `-x value` or `--flag [value]`.
+struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    /// `flags` are the options allowed, with whether they take a value.
+    fn parse(args: &[String], flags: &[(&str, bool)]) -> Result<Self, CliError> {
+        let mut parsed = Args {
+            positional: vec![],
+            options: vec![],
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if !arg.starts_with('-') || arg.parse::<isize>().is_ok() {
+                parsed.positional.push(arg.clone());
+                continue;
+            }
+
+            let (_, takes_value) = flags
+                .iter()
+                .find(|(flag, _)| flag == arg)
+                .ok_or_else(|| usage(format!("unknown option {arg}")))?;
+            let value = match takes_value {
+                true => Some(
+                    args.next()
+                        .ok_or_else(|| usage(format!("{arg} needs a value")))?
+                        .clone(),
+                ),
+                false => None,
+            };
+            parsed.options.push((arg.clone(), value));
+        }
+
+        Ok(parsed)
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(flag, _)| flag == name)
+    }
+
+    /// The value given last to `name`.
+    fn value(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(flag, _)| flag == name)
+            .and_then(|(_, value)| value.as_deref())
+    }
+
+    fn number<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>, CliError> {
+        self.value(name)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| usage(format!("{name} expects a number, not {value}")))
+            })
+            .transpose()
+    }
+
+    /// The environment `--env` names. A name that's neither a preset nor a
+    /// .toml file is a usage error, a file that doesn't load isn't.
+    fn environment(&self) -> Result<Option<Environment>, CliError> {
+        let Some(name) = self.value("--env") else {
+            return Ok(None);
+        };
+        match Environment::find(name) {
+            Err(err) if !name.ends_with(".toml") => Err(usage(err)),
+            found => Ok(Some(found?)),
+        }
+    }
+
+    /// The rules given by the pMARS flags, over `--env` or its defaults.
+    fn rules(&self) -> Result<BattleConfig, CliError> {
+        let mut config = match self.environment()? {
+            Some(environment) => environment.battle,
+            None => {
+                let mut config = BattleConfig::preset("94nop").unwrap();
+                config.rounds = 1;
+                config
+            }
+        };
+
+        for (flag, field) in [
+            ("-r", &mut config.rounds),
+            ("-s", &mut config.core_size),
+            ("-c", &mut config.max_cycles),
+            ("-p", &mut config.max_processes),
+            ("-l", &mut config.max_length),
+            ("-d", &mut config.min_distance),
+        ] {
+            if let Some(value) = self.number(flag)? {
+                *field = value;
+            }
+        }
+
+        if config.core_size == 0 {
+            return Err(usage("the core needs at least one cell"));
+        }
+        Ok(config)
+    }
+}
+
+const RULES: [(&str, bool); 7] = [
+    ("--env", true),
+    ("-r", true),
+    ("-s", true),
+    ("-c", true),
+    ("-p", true),
+    ("-l", true),
+    ("-d", true),
+];
+
+fn with_rules<'a>(flags: &[(&'a str, bool)]) -> Vec<(&'a str, bool)> {
+    RULES.iter().chain(flags).copied().collect()
+}
+
+fn load_warriors(paths: &[String], core_size: usize) -> Result<Vec<Warrior>, CliError> {
+    if paths.is_empty() {
+        return Err(usage("no warriors given"));
+    }
+    Ok(paths
+        .iter()
+        .map(|path| Warrior::load(path, core_size))
+        .collect::<Result<_, _>>()?)
+}
+
+fn battle(args: &[String], out: &mut impl Write) -> Result<(), CliError> {
+    let args = Args::parse(args, &with_rules(&[("--seed", true), ("--json", false)]))?;
+    let config = args.rules()?;
+    let warriors = load_warriors(&args.positional, config.core_size)?;
+
+    let mut batch = Batch::new(config.clone());
+    batch.seed = args.number("--seed")?;
+    let result = batch
+        .run(&warriors, &[(0..warriors.len()).collect()])?
+        .remove(0);
+
+    if args.flag("--json") {
+        let warriors: Vec<String> = warriors
+            .iter()
+            .enumerate()
+            .map(|(i, w)| {
+                format!(
+                    "{{\"name\":{},\"wins\":{},\"losses\":{},\"ties\":{},\"score\":{}}}",
+                    json_string(&w.name),
+                    result.wins[i],
+                    result.losses[i],
+                    result.ties[i],
+                    result.scores[i]
+                )
+            })
+            .collect();
+        writeln!(
+            out,
+            "{{\"rounds\":{},\"warriors\":[{}]}}",
+            config.rounds,
+            warriors.join(",")
+        )?;
+    } else {
+        for (warrior, score) in warriors.iter().zip(&result.scores) {
+            writeln!(out, "{} scores {score}", warrior.name)?;
+        }
+        // rounds nobody won outright are ties
+        let wins: Vec<String> = result.wins.iter().map(|w| w.to_string()).collect();
+        let ties = config.rounds - result.wins.iter().sum::<usize>();
+        writeln!(out, "Results: {} {ties}", wins.join(" "))?;
+    }
+
+    Ok(())
+}
+
+fn tournament(args: &[String], out: &mut impl Write) -> Result<(), CliError> {
+    let flags = with_rules(&[("--melee", true), ("--seed", true), ("--format", true)]);
+    let args = Args::parse(args, &flags)?;
+    let [dir] = &args.positional[..] else {
+        return Err(usage("tournament takes one directory"));
+    };
+
+    let mut tournament = Tournament::load_dir(dir, args.rules()?)?;
+    tournament.seed = args.number("--seed")?;
+    if let Some(melee) = args.number("--melee")? {
+        tournament.melee_size = melee;
+    }
+
+    let result = tournament.run()?;
+    match args.value("--format").unwrap_or("text") {
+        "text" => write!(out, "{}", result.to_text())?,
+        "csv" => write!(out, "{}", result.to_csv())?,
+        "json" => writeln!(out, "{}", result.to_json())?,
+        format => return Err(usage(format!("unknown format {format}"))),
+    }
+
+    Ok(())
+}
+
+fn asm(args: &[String], out: &mut impl Write) -> Result<(), CliError> {
+    let args = Args::parse(args, &[("-s", true), ("--env", true)])?;
+    let [path] = &args.positional[..] else {
+        return Err(usage("asm takes one warrior"));
+    };
+
+    let core_size = match (args.number("-s")?, args.environment()?) {
+        (Some(core_size), _) => core_size,
+        (None, Some(environment)) => environment.battle.core_size,
+        (None, None) => 8000,
+    };
+    let warrior = Warrior::load(path, core_size)?;
+    write!(out, "{}", warrior.to_load_file())?;
+    Ok(())
+}
+
+fn debug(args: &[String], input: &mut impl BufRead, out: &mut impl Write) -> Result<(), CliError> {
+    let args = Args::parse(args, &with_rules(&[("--seed", true)]))?;
+    let config = args.rules()?;
+    let warriors = load_warriors(&args.positional, config.core_size)?;
+
+    let mut rng = match args.number("--seed")? {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let mut core_config = config.core_config();
+    for warrior in warriors.iter() {
+        core_config.deploy_with_rng(warrior.clone(), None, &mut rng)?;
+    }
+    let mut runtime = core_config.brawl();
+
+    for warrior in runtime.warriors.iter() {
+        writeln!(
+            out,
+            "{} starts at {}",
+            warrior.name,
+            warrior.get_counters()[0].val
+        )?;
+    }
+
+    let last_standing = if warriors.len() > 1 { 1 } else { 0 };
+    let max_steps = config.max_cycles * warriors.len();
+    let mut steps = 0;
+    let over = |runtime: &crate::core::CoreRuntime, steps: usize| {
+        runtime.warriors.len() <= last_standing || steps >= max_steps
+    };
+
+    let mut line = String::new();
+    while !over(&runtime, steps) {
+        // where the next instruction is, and whose
+        let (name, pc) = {
+            let warrior = &runtime.warriors[0];
+            (warrior.name.clone(), warrior.get_counters()[0])
+        };
+        writeln!(
+            out,
+            "{steps:>6} {name} {:>5}: {}",
+            pc.val,
+            runtime.get_instruction_at(&pc)
+        )?;
+
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            break;
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let number = |i: usize| -> Result<Option<usize>, CliError> {
+            words
+                .get(i)
+                .map(|w| w.parse().map_err(|_| usage(format!("{w} isn't a number"))))
+                .transpose()
+        };
+
+        match words.first().copied().unwrap_or("s") {
+            "s" | "step" => {
+                for _ in 0..number(1)?.unwrap_or(1) {
+                    if over(&runtime, steps) {
+                        break;
+                    }
+                    runtime.tick();
+                    steps += 1;
+                }
+            }
+            "c" | "continue" => {
+                while !over(&runtime, steps) {
+                    runtime.tick();
+                    steps += 1;
+                }
+            }
+            "l" | "list" => {
+                let from = number(1)?.unwrap_or(pc.val.saturating_sub(5));
+                let to = number(2)?.unwrap_or(from + 10).min(config.core_size);
+                for cell in from..to {
+                    let at = crate::utils::ModUsize::new(cell as isize, config.core_size);
+                    write!(out, "{cell:>5} {}", runtime.get_instruction_at(&at))?;
+                    for warrior in runtime.warriors.iter() {
+                        if warrior.get_counters().contains(&at) {
+                            write!(out, " < {}", warrior.name)?;
+                        }
+                    }
+                    writeln!(out)?;
+                }
+            }
+            "p" | "processes" => {
+                for warrior in runtime.warriors.iter() {
+                    let queue: Vec<String> = warrior
+                        .get_counters()
+                        .iter()
+                        .map(|pc| pc.val.to_string())
+                        .collect();
+                    writeln!(out, "{}: {}", warrior.name, queue.join(" "))?;
+                }
+            }
+            "q" | "quit" => return Ok(()),
+            "h" | "help" => writeln!(out, "{DEBUG_HELP}")?,
+            other => writeln!(out, "unknown command {other}\n{DEBUG_HELP}")?,
+        }
+    }
+
+    let alive: Vec<&str> = runtime
+        .warriors
+        .iter()
+        .filter(|w| !w.dead())
+        .map(|w| w.name.as_str())
+        .collect();
+    writeln!(out, "{steps:>6} over, alive: {}", alive.join(" "))?;
+    Ok(())
+}
+
+/// Points `config` at the checkpoint directory and at log files in the log
+/// directory, `IslandsConfig::new` then gives every island its own.
+fn log_paths(config: &mut EvolutionConfig, checkpoint: Option<&Path>, log: Option<&Path>) {
+    config.checkpoint = checkpoint.map(Path::to_path_buf);
+    config.statistics = log.map(|dir| dir.join("statistics.csv"));
+    config.lineage = log.map(|dir| dir.join("lineage.csv"));
+}
+
+/// Runs `islands` to the end, or resumes them from their checkpoints first,
+/// printing every report. Returns them, and the best warrior of the last
+/// generation with its score.
+fn evolve_islands(
+    islands: IslandsConfig,
+    resume: bool,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(Islands, Option<(f64, Warrior)>), CliError> {
+    if let Some(dir) = islands.islands[0]
+        .statistics
+        .as_ref()
+        .and_then(|p| p.parent())
+    {
+        fs::create_dir_all(dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+    }
+
+    let mut islands = match resume {
+        true => {
+            let resumed = islands
+                .islands
+                .iter()
+                .map(|config| {
+                    let dir = config
+                        .checkpoint
+                        .as_ref()
+                        .ok_or_else(|| usage("resuming needs a checkpoint directory"))?;
+                    Ok(Evolution::resume(config.clone(), dir)?)
+                })
+                .collect::<Result<Vec<_>, CliError>>()?;
+            Islands::with_islands(islands, resumed)
+        }
+        false => Islands::new(islands)?,
+    };
+
+    let mut best: Option<(f64, Warrior)> = None;
+    while islands.generation() < islands.config.generations {
+        for (i, reports) in islands.epoch()?.into_iter().enumerate() {
+            for report in reports {
+                print_report(i, &report, json, out)?;
+
+                if report.generation + 1 == islands.config.generations
+                    && best
+                        .as_ref()
+                        .is_none_or(|(score, _)| report.best_score > *score)
+                {
+                    best = Some((report.best_score, report.best));
+                }
+            }
+        }
+    }
+
+    Ok((islands, best))
+}
+
+fn print_report(
+    island: usize,
+    report: &GenerationReport,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), CliError> {
+    match json {
+        true => writeln!(out, "{{\"island\":{island},{}", &report.to_json()[1..])?,
+        false => writeln!(
+            out,
+            "generation {} island {island}: best {:.1}, mean {:.1}",
+            report.generation, report.best_score, report.mean_score
+        )?,
+    }
+    Ok(())
+}
+
+/// The island experiment the binary used to run on its own.
+fn run(args: &[String], out: &mut impl Write) -> Result<(), CliError> {
+    const ISLANDS: usize = 4;
+
+    let args = Args::parse(
+        args,
+        &[
+            ("--env", true),
+            ("--benchmark", true),
+            ("--checkpoint", true),
+            ("--resume", false),
+            ("--log", true),
+            ("--ciao", false),
+        ],
+    )?;
+    if !args.positional.is_empty() {
+        return Err(usage("run takes no warriors"));
+    }
+
+    let battle = match args.environment()? {
+        Some(environment) => environment.battle,
+        None => {
+            let mut battle = BattleConfig::new(8000);
+            battle.max_cycles = 500;
+            battle
+        }
+    };
+    let core_size = battle.core_size;
+
+    let mut config = EvolutionConfig::new(battle);
+    config.hall_of_fame = Some(HallOfFameConfig::new());
+    if let Some(dir) = args.value("--benchmark") {
+        // warriors to evolve against, instead of each other
+        config.benchmark = Some(Benchmark::load_dir(dir, core_size)?);
+    }
+    let checkpoint = args.value("--checkpoint").map(PathBuf::from);
+    let log = args.value("--log").map(PathBuf::from);
+    log_paths(&mut config, checkpoint.as_deref(), log.as_deref());
+
+    // islands that explore more or less, each saved to its own directory
+    let mut islands = IslandsConfig::new(config, ISLANDS);
+    for (i, island) in islands.islands.iter_mut().enumerate() {
+        island.mutation_rate = 0.6 + 0.4 * i as f64 / (ISLANDS - 1) as f64;
+    }
+
+    let (islands, best) = evolve_islands(islands, args.flag("--resume"), false, out)?;
+
+    if let Some((_, best)) = best {
+        writeln!(out)?;
+        for (i, instruction) in best.body.iter().enumerate() {
+            let org = if i == best.org { "<" } else { "" };
+            writeln!(out, "{instruction} {org}")?;
+        }
+    }
+
+    // how each island's champions fare against the ones before them
+    if args.flag("--ciao") {
+        for (i, island) in islands.islands.iter().enumerate() {
+            if let Some(hall_of_fame) = &island.hall_of_fame {
+                let batch = Batch::new(island.config.battle.clone());
+                writeln!(out, "\nisland {i}\n{}", hall_of_fame.ciao(&batch)?)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Evolves warriors as a config file describes, see `islands_config`.
+fn evolve(args: &[String], out: &mut impl Write) -> Result<(), CliError> {
+    let args = Args::parse(
+        args,
+        &[("--config", true), ("--resume", false), ("--json", false)],
+    )?;
+    let path = args
+        .value("--config")
+        .ok_or_else(|| usage("evolve needs a --config file"))?;
+    let source = fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+    let islands = islands_config(&source).map_err(|err| format!("{path}: {err}"))?;
+
+    let json = args.flag("--json");
+    let (_, best) = evolve_islands(islands, args.flag("--resume"), json, out)?;
+
+    if let Some((score, best)) = best {
+        match json {
+            true => writeln!(
+                out,
+                "{{\"best_score\":{score},\"best\":{}}}",
+                json_string(&best.to_load_file())
+            )?,
+            false => write!(out, "\n{}", best.to_load_file())?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads an evolution config file:
+///
+/// ```toml
+/// population = 20          # and every other number of EvolutionConfig
+/// selection = "tournament" # "truncation" or "roulette" too
+/// tournament_size = 3      # or truncation = 0.2
+/// seed = 1
+/// checkpoint = "dir"       # every island saved under dir/island_<i>
+/// log = "dir"              # statistics and lineage of every island
+///
+/// environment = "tiny"     # a preset or .toml file, see `Environment`,
+///                          # or an [environment] table of its keys
+/// [battle]                 # or the BattleConfig fields, 94nop if neither
+/// core_size = 8000         # is given
+///
+/// [mutation]               # weights by operator name, max_delta, constants
+/// [crossover]              # weights by operator name, align_org
+/// [benchmark]              # dir, rounds and pool_weight
+/// [hall_of_fame]           # size, sampling = "all", "latest" or "random",
+///                          # sample and weight
+/// [islands]                # count, interval, topology = "ring" or
+///                          # "fully_connected"
+/// ```
+pub fn islands_config(source: &str) -> Result<IslandsConfig, String> {
+    let table = settings::parse(source)?;
+    table.expect_only(&[
+        "population",
+        "generations",
+        "warrior_length",
+        "selection",
+        "tournament_size",
+        "truncation",
+        "elitism",
+        "mutation_rate",
+        "crossover_rate",
+        "seed",
+        "checkpoint",
+        "log",
+        "environment",
+        "battle",
+        "mutation",
+        "crossover",
+        "benchmark",
+        "hall_of_fame",
+        "islands",
+    ])?;
+
+    let battle = match (table.get("environment"), table.table("battle")?) {
+        (Some(_), Some(_)) => return Err("give either environment or battle, not both".into()),
+        (Some(environment), None) => match environment.as_str() {
+            Some(name) => Environment::find(name)?.battle,
+            None => {
+                let environment = table.table("environment")?.unwrap_or_default();
+                Environment::from_table(&environment)?.battle
+            }
+        },
+        (None, Some(battle)) => battle_config(&battle)?,
+        (None, None) => BattleConfig::preset("94nop").unwrap(),
+    };
+    let mut config = EvolutionConfig::new(battle.clone());
+
+    for (key, field) in [
+        ("population", &mut config.population),
+        ("generations", &mut config.generations),
+        ("warrior_length", &mut config.warrior_length),
+        ("elitism", &mut config.elitism),
+    ] {
+        if let Some(value) = table.usize(key)? {
+            *field = value;
+        }
+    }
+    for (key, field) in [
+        ("mutation_rate", &mut config.mutation_rate),
+        ("crossover_rate", &mut config.crossover_rate),
+    ] {
+        if let Some(value) = table.float(key)? {
+            *field = value;
+        }
+    }
+    config.seed = table.integer("seed")?.map(|seed| seed as u64);
+
+    config.selection = match table.string("selection")? {
+        None | Some("tournament") => {
+            Selection::Tournament(table.usize("tournament_size")?.unwrap_or(3))
+        }
+        Some("truncation") => Selection::Truncation(table.float("truncation")?.unwrap_or(0.2)),
+        Some("roulette") => Selection::Roulette,
+        Some(other) => return Err(format!("unknown selection {other}")),
+    };
+
+    if let Some(mutation) = table.table("mutation")? {
+        let names: Vec<String> = MutationOperator::ALL
+            .iter()
+            .map(|op| op.to_string())
+            .collect();
+        let mut known: Vec<&str> = names.iter().map(String::as_str).collect();
+        known.extend(["max_delta", "constants"]);
+        mutation.expect_only(&known)?;
+
+        for operator in MutationOperator::ALL {
+            if let Some(weight) = mutation.float(&operator.to_string())? {
+                config.mutation.set_weight(operator, weight);
+            }
+        }
+        if let Some(max_delta) = mutation.usize("max_delta")? {
+            config.mutation.max_delta = max_delta;
+        }
+        if let Some(constants) = mutation.array("constants")? {
+            config.mutation.constants = constants
+                .iter()
+                .map(|value| match value.as_integer() {
+                    Some(i) if i >= 0 => Ok(i as usize),
+                    _ => Err("mutation.constants should be positive integers".to_string()),
+                })
+                .collect::<Result<_, _>>()?;
+        }
+    }
+
+    if let Some(crossover) = table.table("crossover")? {
+        let names: Vec<String> = CrossoverOperator::ALL
+            .iter()
+            .map(|op| op.to_string())
+            .collect();
+        let mut known: Vec<&str> = names.iter().map(String::as_str).collect();
+        known.push("align_org");
+        crossover.expect_only(&known)?;
+
+        for operator in CrossoverOperator::ALL {
+            if let Some(weight) = crossover.float(&operator.to_string())? {
+                config.crossover.set_weight(operator, weight);
+            }
+        }
+        if let Some(align_org) = crossover.boolean("align_org")? {
+            config.crossover.align_org = align_org;
+        }
+    }
+
+    let checkpoint = table.string("checkpoint")?.map(PathBuf::from);
+    let log = table.string("log")?.map(PathBuf::from);
+    log_paths(&mut config, checkpoint.as_deref(), log.as_deref());
+
+    if let Some(benchmark) = table.table("benchmark")? {
+        benchmark.expect_only(&["dir", "rounds", "pool_weight"])?;
+        let dir = benchmark.string("dir")?.ok_or("benchmark.dir is missing")?;
+        let mut set = Benchmark::load_dir(dir, battle.core_size)?;
+        if let Some(rounds) = benchmark.usize("rounds")? {
+            set.rounds = rounds;
+        }
+        config.benchmark = Some(set);
+        config.pool_weight = benchmark.float("pool_weight")?.unwrap_or(0.);
+    }
+
+    if let Some(hall_of_fame) = table.table("hall_of_fame")? {
+        hall_of_fame.expect_only(&["size", "sampling", "sample", "weight"])?;
+        let mut hof = HallOfFameConfig::new();
+        if let Some(size) = hall_of_fame.usize("size")? {
+            hof.size = size;
+        }
+        let sample = hall_of_fame.usize("sample")?.unwrap_or(10);
+        hof.sampling = match hall_of_fame.string("sampling")? {
+            Some("all") => Sampling::All,
+            Some("latest") => Sampling::Latest(sample),
+            None | Some("random") => Sampling::Random(sample),
+            Some(other) => return Err(format!("unknown hall_of_fame.sampling {other}")),
+        };
+        if let Some(weight) = hall_of_fame.float("weight")? {
+            hof.weight = weight;
+        }
+        config.hall_of_fame = Some(hof);
+    }
+
+    let islands = match table.table("islands")? {
+        Some(islands) => {
+            islands.expect_only(&["count", "interval", "topology"])?;
+            let mut config = IslandsConfig::new(config, islands.usize("count")?.unwrap_or(1));
+            if let Some(interval) = islands.usize("interval")? {
+                config.interval = interval;
+            }
+            config.topology = match islands.string("topology")? {
+                None | Some("ring") => Topology::Ring,
+                Some("fully_connected") => Topology::FullyConnected,
+                Some(other) => return Err(format!("unknown islands.topology {other}")),
+            };
+            config
+        }
+        None => IslandsConfig::new(config, 1),
+    };
+    if islands.islands.is_empty() {
+        return Err("islands.count should be at least 1".into());
+    }
+
+    Ok(islands)
+}
+
+/// A `BattleConfig` out of a table with its fields, 94nop for any missing.
+pub fn battle_config(table: &Table) -> Result<BattleConfig, String> {
+    let mut config = BattleConfig::preset("94nop").unwrap();
+    let keys = [
+        "core_size",
+        "max_cycles",
+        "max_processes",
+        "max_length",
+        "min_distance",
+        "rounds",
+    ];
+    table.expect_only(&keys)?;
+
+    for (key, field) in keys.into_iter().zip([
+        &mut config.core_size,
+        &mut config.max_cycles,
+        &mut config.max_processes,
+        &mut config.max_length,
+        &mut config.min_distance,
+        &mut config.rounds,
+    ]) {
+        if let Some(value) = table.usize(key)? {
+            *field = value;
+        }
+    }
+
+    Ok(config)
+}
@@ -1,21 +1,38 @@
+use rand::Rng;
+
+use self::{
+    handlers::{arithmetic_pipes, dispatch, Flow, Operands},
+    stats::{Collector, WarriorStats},
+};
 use crate::{
     instruction::{
-        field::Field, instruction::Instruction, op_code::OpCode, op_modifier::OpModifier,
+        cell::{wrap, Cell, Mode},
+        instruction::Instruction,
+        op_code::OpCode,
     },
     utils::{modulo, ModUsize},
     warrior::Warrior,
 };
 
+pub(crate) mod handlers;
+pub mod stats;
+
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CoreRuntime {
     pub core_size: usize,
-    pub core: Vec<Instruction>,
+    pub max_processes: usize,
+    /// Packed cells, their values are all below `core_size`.
+    pub core: Vec<Cell>,
     pub warriors: Vec<Warrior>,
+    stats: Option<Box<Collector>>,
 }
 #[derive(Debug, Clone)]
-
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CoreConfig {
     core_size: usize,
+    max_processes: usize,
+    min_distance: usize,
     warrior_data: Vec<(ModUsize, Warrior)>,
 }
 
@@ -25,226 +42,62 @@ impl CoreRuntime {
     }
 
     pub fn tick(&mut self) {
-        if self.warriors.len() == 0 {
-            return;
-        }
-
-        let instruction_counter = self.warriors[0].get_next_instruction_counter();
-
-        let instruction = self.get_instruction_at(&instruction_counter).clone();
-
-        let field_a_solution = instruction.fields[0].solve(self, instruction_counter);
-
-        let instruction_a = self.get_instruction_at(&field_a_solution).clone();
-
-        let field_b_solution = instruction.fields[1].solve(self, instruction_counter);
-
-        let instruction_b = self.get_instruction_at(&field_b_solution).clone();
-
-        // print!("warrior {} is going to execute ", self.warriors[0].name);
-
-        // instruction.print_state();
-
-        // println!("");
-
-        let mut die = false;
-
-        // println!("[debug]: instruction is {:?}", instruction);
-
-        let mut next_instruction = instruction_counter.inc(1);
-
-        match instruction.code {
-            OpCode::DAT => {
-                die = true;
-            }
-            OpCode::MOV => {
-                let (pipes, i_flag) = instruction.get_field_transmisions();
-
-                if i_flag {
-                    self.set_instruction_at(&field_b_solution, instruction_a);
-                } else {
-                    for (i_src, i_dst) in pipes {
-                        let data = instruction_a.get_field(i_src);
-                        self.write_field_val(&field_b_solution, i_dst, *data.get_val());
-                    }
-                }
-            }
-            OpCode::ADD => {
-                let (pipes, _) = instruction.get_field_transmisions();
-
-                for (i_src, i_dst) in pipes {
-                    // println!("[debug]: pipe from {} to {} ", i_src, i_dst);
-                    let operand = *instruction_a.get_field(i_src).get_val();
-                    let old_value = *instruction_b.get_field(i_dst).get_val();
-                    self.write_field_val(&field_b_solution, i_dst, old_value + operand);
-                }
-            }
-            OpCode::SUB => {
-                let (pipes, _) = instruction.get_field_transmisions();
-
-                for (i_src, i_dst) in pipes {
-                    let operand = *instruction_a.get_field(i_src).get_val();
-                    let old_value = *instruction_b.get_field(i_dst).get_val();
-                    self.write_field_val(&field_b_solution, i_dst, old_value - operand);
-                }
-            }
-            OpCode::MUL => {
-                let (pipes, _) = instruction.get_field_transmisions();
-
-                for (i_src, i_dst) in pipes {
-                    let operand = *instruction_a.get_field(i_src).get_val();
-                    let old_value = *instruction_b.get_field(i_dst).get_val();
-                    self.write_field_val(&field_b_solution, i_dst, old_value * operand);
-                }
-            }
-            OpCode::DIV => {
-                let (pipes, _) = instruction.get_field_transmisions();
-
-                for (i_src, i_dst) in pipes {
-                    let operand = *instruction_a.get_field(i_src).get_val();
-                    let old_value = *instruction_b.get_field(i_dst).get_val();
-                    if operand == 0 {
-                        die = true;
-                    } else {
-                        self.write_field_val(&field_b_solution, i_dst, old_value / operand);
-                    }
-                }
-            }
-            OpCode::MOD => {
-                let (pipes, _) = instruction.get_field_transmisions();
-
-                for (i_src, i_dst) in pipes {
-                    let operand = *instruction_a.get_field(i_src).get_val();
-                    let old_value = *instruction_b.get_field(i_dst).get_val();
-                    if operand == 0 {
-                        die = true;
-                    } else {
-                        self.write_field_val(
-                            &field_b_solution,
-                            i_dst,
-                            ModUsize::new(
-                                old_value.val as isize % operand.val as isize,
-                                operand.congruence,
-                            ),
-                        );
-                    }
-                }
-            }
-            OpCode::JMP => next_instruction = field_a_solution,
-            OpCode::JMZ => {
-                let (pipes, _) = instruction.get_field_transmisions();
-
-                let mut jump = true;
-
-                for (_, i_dst) in pipes {
-                    if *instruction_b.get_field(i_dst).get_val() != 0 {
-                        jump = false;
-                    }
-                }
-
-                if jump {
-                    next_instruction = field_a_solution;
-                }
-            }
-            OpCode::JMN => {
-                let (pipes, _) = instruction.get_field_transmisions();
-
-                let mut jump = false;
-
-                for (_, i_dst) in pipes {
-                    if *instruction_b.get_field(i_dst).get_val() != 0 {
-                        jump = true;
-                    }
-                }
-
-                if jump {
-                    next_instruction = field_a_solution;
-                }
-            }
-            OpCode::DJN => {
-                let (pipes, _) = instruction.get_field_transmisions();
-
-                let mut jump = false;
-
-                for (_, i_dst) in pipes {
-                    let val = *instruction_b.get_field(i_dst).get_val() - 1 as usize;
-                    self.write_field_val(&field_b_solution, i_dst, val);
-
-                    if val != 0 {
-                        jump = true;
+        // ICWS '94: the executing process leaves the queue, its successor is
+        // queued afterwards and the SPL child (if any) goes right behind it.
+        let instruction_counter = loop {
+            match self.warriors.first_mut() {
+                None => return,
+                Some(warrior) => match warrior.pop_instruction_counter() {
+                    Some(ic) => break ic,
+                    None => {
+                        self.warriors.remove(0);
                     }
-                }
-
-                if jump {
-                    next_instruction = field_a_solution;
-                }
+                },
             }
-            OpCode::SPL => (),
-            OpCode::CMP | OpCode::SEQ => {
-                let (pipes, _) = instruction.get_field_transmisions();
-
-                let mut jump = true;
-
-                for (i_src, i_dst) in pipes {
-                    let a = *instruction_a.get_field(i_src).get_val();
-                    let b = *instruction_b.get_field(i_dst).get_val();
-                    if a != b {
-                        jump = false;
-                    }
-                }
+        };
 
-                if jump {
-                    next_instruction = next_instruction.inc(1);
-                }
-            }
-            OpCode::SNE => {
-                let (pipes, _) = instruction.get_field_transmisions();
+        let m = self.core_size;
+        let ic = instruction_counter.val;
+        let instruction = self.core[ic];
+        let (modes, values) = (instruction.modes(), instruction.values());
+
+        let a = modes[0].solve(values[0], self, ic);
+        let ira = self.core[a];
+        modes[0].post_increment(values[0], self, ic);
+
+        let b = modes[1].solve(values[1], self, ic);
+        let irb = self.core[b];
+        modes[1].post_increment(values[1], self, ic);
+
+        let operands = Operands {
+            pc: ic,
+            a,
+            b,
+            ira,
+            irb,
+        };
 
-                let mut jump = false;
+        let counter = |val| ModUsize { val, congruence: m };
 
-                for (i_src, i_dst) in pipes {
-                    let a = *instruction_a.get_field(i_src).get_val();
-                    let b = *instruction_b.get_field(i_dst).get_val();
-                    if a != b {
-                        jump = true;
-                    }
-                }
+        let flow = dispatch(instruction.handler())(self, &operands);
+        if self.stats.is_some() {
+            self.record(&instruction, &operands);
+        }
 
-                if jump {
-                    next_instruction = next_instruction.inc(1);
-                }
-            }
-            OpCode::SLT => {
-                let (pipes, _) = instruction.get_field_transmisions();
+        match flow {
+            Flow::Die => (),
+            Flow::Continue(next) => self.warriors[0].new_thread(counter(next)),
+            Flow::Split => {
+                self.warriors[0].new_thread(counter(wrap(ic + 1, m)));
 
-                let mut jump = false;
+                if self.warriors[0].thread_count() < self.max_processes {
+                    self.warriors[0].new_thread(counter(a));
 
-                for (i_src, i_dst) in pipes {
-                    let a = *instruction_a.get_field(i_src).get_val();
-                    let b = *instruction_b.get_field(i_dst).get_val();
-                    if a.val < b.val {
-                        jump = true;
+                    if let Some(stats) = &mut self.stats {
+                        stats.spawned(self.warriors[0].deploy_index);
                     }
                 }
-
-                if jump {
-                    next_instruction = next_instruction.inc(1);
-                }
             }
-            // OpCode::LDP => todo!(),
-            // OpCode::STP => todo!(),
-            OpCode::NOP => (),
-        }
-
-        self.warriors[0].set_last_instruction_counter(next_instruction);
-
-        if let OpCode::SPL = instruction.code {
-            // println!("creating new thread at: {:?}", field_a_solution);
-            self.warriors[0].new_thread(field_a_solution);
-        }
-
-        if die {
-            self.warriors[0].kill_thread();
         }
 
         if self.warriors[0].dead() {
@@ -254,24 +107,66 @@ impl CoreRuntime {
         }
     }
 
-    pub fn get_instruction_at(&self, ptr: &ModUsize) -> &Instruction {
-        &self.core[ptr.val]
+    /// Counts the instruction the current warrior just executed, and the
+    /// cells it wrote.
+    #[cold]
+    fn record(&mut self, instruction: &Cell, operands: &Operands) {
+        let Some(stats) = &mut self.stats else {
+            return;
+        };
+
+        let warrior = self.warriors[0].deploy_index;
+        let pc = operands.pc;
+        stats.executed(warrior, pc);
+
+        for (mode, value) in instruction.modes().iter().zip(instruction.values()) {
+            if matches!(
+                mode,
+                Mode::APredecrement
+                    | Mode::APostincrement
+                    | Mode::BPredecrement
+                    | Mode::BPostincrement
+            ) {
+                stats.wrote(warrior, wrap(pc + value as usize, self.core_size));
+            }
+        }
+
+        let writes = match instruction.code() {
+            OpCode::MOV | OpCode::ADD | OpCode::SUB | OpCode::MUL | OpCode::DJN => true,
+            // a field divided by zero is left alone, the cell is only written
+            // if the other one isn't
+            OpCode::DIV | OpCode::MOD => arithmetic_pipes(instruction.get_modifier())
+                .iter()
+                .any(|&(src, _)| operands.ira.value(src) != 0),
+            _ => false,
+        };
+        if writes {
+            stats.wrote(warrior, operands.b);
+        }
     }
 
-    pub fn get_mut_instruction_at(&mut self, ptr: &ModUsize) -> &mut Instruction {
-        &mut self.core[ptr.val]
+    /// Starts or stops collecting `WarriorStats`, from the next round
+    /// brawled into this core on.
+    pub fn collect_stats(&mut self, on: bool) {
+        if !on {
+            self.stats = None;
+        } else if self.stats.is_none() {
+            self.stats = Some(Box::default());
+        }
     }
 
-    fn set_instruction_at(&mut self, ptr: &ModUsize, instruction: Instruction) {
-        self.core[ptr.val] = instruction;
+    /// Statistics of the round being played by deploy index, if collected.
+    pub fn stats(&self) -> Option<&[WarriorStats]> {
+        self.stats.as_ref().map(|stats| stats.stats.as_slice())
     }
 
-    fn write_field_val(&mut self, ptr: &ModUsize, i_field: usize, data: ModUsize) {
-        self.core[ptr.val].fields[i_field].set_val(data)
+    /// The instruction at `ptr`, unpacked from its cell.
+    pub fn get_instruction_at(&self, ptr: &ModUsize) -> Instruction {
+        self.core[ptr.val].to_instruction(self.core_size)
     }
 
     #[cfg(not(tarpaulin_include))]
-    pub(crate) fn print_state(&self, range: Option<std::ops::Range<usize>>) {
+    pub fn print_state(&self, range: Option<std::ops::Range<usize>>) {
         for w in &self.warriors {
             println!("{}: {:?}", w.name, w.get_counters())
         }
@@ -285,7 +180,7 @@ impl CoreRuntime {
         for (i, cell) in self.core.iter().enumerate() {
             if range.contains(&i) {
                 //print!("{i:0>6}: ");
-                cell.print_state();
+                cell.to_instruction(self.core_size).print_state();
                 for warr in self.warriors.iter() {
                     warr.print_state_at(i);
                 }
@@ -300,99 +195,215 @@ impl CoreConfig {
     pub fn new(core_size: usize) -> Self {
         Self {
             core_size,
+            max_processes: core_size,
+            min_distance: 0,
             warrior_data: vec![],
         }
     }
 
+    /// Minimum distance kept between the load addresses of any two warriors.
+    pub fn set_min_distance(&mut self, min_distance: usize) {
+        self.min_distance = min_distance;
+    }
+
+    pub fn set_max_processes(&mut self, max_processes: usize) {
+        self.max_processes = max_processes;
+    }
+
     pub fn brawl(&self) -> CoreRuntime {
-        let mut core = vec![
-            Instruction {
-                ptr_range: 0,
-                core_size: 0,
-                code: OpCode::DAT,
-                fields: [
-                    Field::Direct(ModUsize::new(0, self.core_size)),
-                    Field::Direct(ModUsize::new(0, self.core_size))
-                ],
-                modifier: OpModifier::Default,
-            };
-            self.core_size as usize
-        ];
+        let mut runtime = CoreRuntime {
+            core_size: self.core_size,
+            max_processes: self.max_processes,
+            core: vec![],
+            warriors: vec![],
+            stats: None,
+        };
+
+        self.brawl_into(&mut runtime);
+
+        runtime
+    }
+
+    /// Like `brawl`, but reuses the core and warrior buffers of `runtime`
+    /// instead of allocating new ones.
+    pub fn brawl_into(&self, runtime: &mut CoreRuntime) {
+        runtime.core_size = self.core_size;
+        runtime.max_processes = self.max_processes;
+
+        runtime.core.clear();
+        runtime.core.resize(self.core_size, Cell::EMPTY);
 
         for (deploy_position, warrior) in self.warrior_data.iter() {
             for (i, op) in warrior.body.iter().enumerate() {
-                core[modulo(deploy_position.val + i, self.core_size)] = op.to_owned();
+                runtime.core[modulo(deploy_position.val + i, self.core_size)] =
+                    Cell::from_instruction(op, self.core_size);
             }
         }
 
-        // println!("BRAAAAWL!");
-
-        CoreRuntime {
-            core_size: self.core_size,
-            core,
-            warriors: self.warrior_data.iter().map(|(_, e)| e).cloned().collect(),
+        runtime.warriors.clear();
+        runtime
+            .warriors
+            .extend(self.warrior_data.iter().map(|(_, e)| e).cloned());
+
+        if let Some(stats) = &mut runtime.stats {
+            let bodies = self
+                .warrior_data
+                .iter()
+                .map(|(position, warrior)| (position.val, warrior.body.len()));
+            stats.reset(bodies, self.core_size);
         }
     }
+
     pub fn deploy(
+        &mut self,
+        warrior: Warrior,
+        input_position: Option<ModUsize>,
+    ) -> Result<(), String> {
+        self.deploy_with_rng(warrior, input_position, &mut rand::thread_rng())
+    }
+
+    /// Like `deploy`, drawing random positions from `rng`.
+    pub fn deploy_with_rng(
         &mut self,
         mut warrior: Warrior,
         input_position: Option<ModUsize>,
+        rng: &mut impl Rng,
     ) -> Result<(), String> {
         let w_len = warrior.body.len();
         let core_size = self.core_size;
 
+        warrior.deploy_index = self.warrior_data.len();
+
         if let Some(deploy_position) = input_position {
-            for (position, warrior) in self.warrior_data.iter() {
-                if check_segment_colision(&deploy_position, w_len, position, warrior.body.len()) {
-                    return Err("Forced deploy position was already ocupied".into());
-                }
+            if !self.is_free(&deploy_position, w_len) {
+                return Err("Forced deploy position was already ocupied".into());
             }
 
             warrior.new_thread(deploy_position + warrior.org);
 
             self.warrior_data.push((deploy_position, warrior));
 
-            return Ok(());
+            Ok(())
         } else {
-            for _ in 0..self.core_size * 2 {
-                let deploy_position = ModUsize::rand(core_size, 0..core_size);
-                let mut valid_pos = true;
-
-                for (position, warrior) in self.warrior_data.iter() {
-                    if check_segment_colision(&deploy_position, w_len, position, warrior.body.len())
-                    {
-                        valid_pos = false;
-                        break;
-                    }
-                }
+            let placed: Vec<(ModUsize, usize)> = self
+                .warrior_data
+                .iter()
+                .map(|(position, warrior)| (*position, warrior.body.len()))
+                .collect();
+            let deploy_position =
+                random_position(&placed, w_len, core_size, self.min_distance, rng)?;
 
-                if valid_pos {
-                    warrior.new_thread(deploy_position + warrior.org);
+            warrior.new_thread(deploy_position + warrior.org);
+            self.warrior_data.push((deploy_position, warrior));
 
-                    self.warrior_data.push((deploy_position, warrior));
+            Ok(())
+        }
+    }
 
-                    return Ok(());
-                }
+    fn is_free(&self, deploy_position: &ModUsize, w_len: usize) -> bool {
+        self.warrior_data.iter().all(|(position, warrior)| {
+            fits(
+                deploy_position,
+                w_len,
+                position,
+                warrior.body.len(),
+                self.min_distance,
+            )
+        })
+    }
+}
+
+impl CoreRuntime {
+    /// Sets up a round between `warriors` placed at random, drawing the same
+    /// positions `CoreConfig::deploy_with_rng` would. The warriors aren't
+    /// copied in, the runtime only keeps their queues, in the buffers of the
+    /// round before.
+    pub(crate) fn load_with_rng(
+        &mut self,
+        warriors: &[&Warrior],
+        max_processes: usize,
+        min_distance: usize,
+        rng: &mut impl Rng,
+    ) -> Result<(), String> {
+        let core_size = self.core_size;
+        let mut placed: Vec<(ModUsize, usize)> = Vec::with_capacity(warriors.len());
+        for warrior in warriors {
+            let len = warrior.body.len();
+            let position = random_position(&placed, len, core_size, min_distance, rng)?;
+            placed.push((position, len));
+        }
+
+        self.max_processes = max_processes;
+        self.core.clear();
+        self.core.resize(core_size, Cell::EMPTY);
+        for ((position, _), warrior) in placed.iter().zip(warriors) {
+            for (i, op) in warrior.body.iter().enumerate() {
+                self.core[modulo(position.val + i, core_size)] =
+                    Cell::from_instruction(op, core_size);
             }
+        }
 
-            Err("Core is likely full of warriors allready, cant deploy any more".into())
+        // survivors of the last round lend their queues to the first warriors
+        self.warriors.truncate(warriors.len());
+        for (i, ((position, _), warrior)) in placed.iter().zip(warriors).enumerate() {
+            if i == self.warriors.len() {
+                self.warriors.push(Warrior::process_holder());
+            }
+            self.warriors[i].restart(i, *position + warrior.org);
         }
+
+        if let Some(stats) = &mut self.stats {
+            stats.reset(placed.iter().map(|(p, len)| (p.val, *len)), core_size);
+        }
+        Ok(())
     }
 }
 
+/// A random load address for a warrior `len` long that keeps clear of those
+/// `placed` so far, given up on after twice the core size draws.
+fn random_position(
+    placed: &[(ModUsize, usize)],
+    len: usize,
+    core_size: usize,
+    min_distance: usize,
+    rng: &mut impl Rng,
+) -> Result<ModUsize, String> {
+    for _ in 0..core_size * 2 {
+        let position = ModUsize::new(rng.gen_range(0..core_size) as isize, core_size);
+
+        if placed
+            .iter()
+            .all(|(other, other_len)| fits(&position, len, other, *other_len, min_distance))
+        {
+            return Ok(position);
+        }
+    }
+
+    Err("Core is likely full of warriors allready, cant deploy any more".into())
+}
+
+/// Whether two warriors overlap nowhere and load at least `min_distance`
+/// apart both ways round the core.
+fn fits(
+    start_a: &ModUsize,
+    len_a: usize,
+    start_b: &ModUsize,
+    len_b: usize,
+    min_distance: usize,
+) -> bool {
+    let distance = (*start_a - *start_b).val;
+
+    !check_segment_colision(start_a, len_a, start_b, len_b)
+        && distance >= min_distance
+        && start_a.congruence - distance >= min_distance
+}
+
 fn check_segment_colision(
     start_a: &ModUsize,
     len_a: usize,
     start_b: &ModUsize,
     len_b: usize,
 ) -> bool {
-    if len_a < len_b {
-        check_segment_colision(&start_b, len_b, &start_a, len_a)
-    } else {
-        // len_a > len_b
-        let n_start_b = start_b.inc(-(start_a.val as isize));
-        let n_end_b = start_b.inc(len_b as isize);
-
-        (n_start_b.val <= len_b) || (n_end_b.val <= len_b)
-    }
+    // one of the segments has to start inside the other
+    (*start_b - *start_a).val < len_a || (*start_a - *start_b).val < len_b
 }
@@ -0,0 +1,341 @@
+/*
+    One function per opcode and resolved modifier, so the runtime never
+    looks at the modifier while executing. Cells carry the index of their
+    handler, computed once by `decode` when the cell is built; whole cell
+    writes copy it along and field writes can't change it.
+*/
+
+use crate::instruction::{
+    cell::{wrap, Cell},
+    op_code::OpCode,
+    op_modifier::OpModifier,
+};
+
+use super::CoreRuntime;
+
+/// The evaluated operands of the executing instruction.
+pub(crate) struct Operands {
+    pub pc: usize,
+    /// Address the A operand points to.
+    pub a: usize,
+    /// Address the B operand points to, where results are written.
+    pub b: usize,
+    /// Copy of the A operand's cell, taken before postincrement.
+    pub ira: Cell,
+    /// Copy of the B operand's cell, taken before postincrement.
+    pub irb: Cell,
+}
+
+/// What happens to the executing process.
+pub(crate) enum Flow {
+    Die,
+    Continue(usize),
+    /// Continue at the next instruction and, if there's room, start a new
+    /// process at the A address.
+    Split,
+}
+
+type Handler = fn(&mut CoreRuntime, &Operands) -> Flow;
+
+const MODIFIERS: usize = 7;
+
+/// The handler index of `code`, with `modifier` already resolved.
+pub(crate) const fn decode(code: OpCode, modifier: OpModifier) -> u8 {
+    (code as usize * MODIFIERS + modifier as usize) as u8
+}
+
+#[inline(always)]
+pub(crate) fn dispatch(handler: u8) -> Handler {
+    HANDLERS.as_flattened()[handler as usize]
+}
+
+// field pairs each modifier works on, (source, destination)
+const A: &[(usize, usize)] = &[(0, 0)];
+const B: &[(usize, usize)] = &[(1, 1)];
+const AB: &[(usize, usize)] = &[(0, 1)];
+const BA: &[(usize, usize)] = &[(1, 0)];
+const F: &[(usize, usize)] = &[(0, 0), (1, 1)];
+const X: &[(usize, usize)] = &[(0, 1), (1, 0)];
+
+/// The field pairs of an arithmetic instruction, whose .I form is .F.
+pub(crate) fn arithmetic_pipes(modifier: OpModifier) -> &'static [(usize, usize)] {
+    match modifier {
+        OpModifier::A => A,
+        OpModifier::B => B,
+        OpModifier::AB => AB,
+        OpModifier::BA => BA,
+        OpModifier::X => X,
+        OpModifier::F | OpModifier::I | OpModifier::Default => F,
+    }
+}
+
+fn next(core: &CoreRuntime, pc: usize) -> usize {
+    wrap(pc + 1, core.core_size)
+}
+
+#[inline(always)]
+fn mov(core: &mut CoreRuntime, ops: &Operands, pipes: &[(usize, usize)]) -> Flow {
+    for &(src, dst) in pipes {
+        core.core[ops.b].set_value(dst, ops.ira.value(src));
+    }
+
+    Flow::Continue(next(core, ops.pc))
+}
+
+#[inline(always)]
+fn arithmetic(
+    core: &mut CoreRuntime,
+    ops: &Operands,
+    pipes: &[(usize, usize)],
+    op: impl Fn(usize, usize, usize) -> Option<usize>,
+) -> Flow {
+    let m = core.core_size;
+    let mut alive = true;
+
+    for &(src, dst) in pipes {
+        let operand = ops.ira.value(src) as usize;
+        let old_value = ops.irb.value(dst) as usize;
+
+        // a division by zero leaves its field alone, the other one is still done
+        match op(old_value, operand, m) {
+            Some(value) => core.core[ops.b].set_value(dst, value as u32),
+            None => alive = false,
+        }
+    }
+
+    if alive {
+        Flow::Continue(next(core, ops.pc))
+    } else {
+        Flow::Die
+    }
+}
+
+fn add(b: usize, a: usize, m: usize) -> Option<usize> {
+    Some(wrap(b + a, m))
+}
+
+fn sub(b: usize, a: usize, m: usize) -> Option<usize> {
+    Some(wrap(b + m - a, m))
+}
+
+fn mul(b: usize, a: usize, m: usize) -> Option<usize> {
+    Some(b * a % m)
+}
+
+fn div(b: usize, a: usize, _: usize) -> Option<usize> {
+    (a != 0).then(|| b / a)
+}
+
+fn rem(b: usize, a: usize, _: usize) -> Option<usize> {
+    (a != 0).then(|| b % a)
+}
+
+#[inline(always)]
+fn jump_if(core: &CoreRuntime, ops: &Operands, jump: bool) -> Flow {
+    if jump {
+        Flow::Continue(ops.a)
+    } else {
+        Flow::Continue(next(core, ops.pc))
+    }
+}
+
+#[inline(always)]
+fn jmz(core: &mut CoreRuntime, ops: &Operands, pipes: &[(usize, usize)]) -> Flow {
+    let zero = pipes.iter().all(|&(_, dst)| ops.irb.value(dst) == 0);
+    jump_if(core, ops, zero)
+}
+
+#[inline(always)]
+fn jmn(core: &mut CoreRuntime, ops: &Operands, pipes: &[(usize, usize)]) -> Flow {
+    let non_zero = pipes.iter().any(|&(_, dst)| ops.irb.value(dst) != 0);
+    jump_if(core, ops, non_zero)
+}
+
+#[inline(always)]
+fn djn(core: &mut CoreRuntime, ops: &Operands, pipes: &[(usize, usize)]) -> Flow {
+    let m = core.core_size;
+
+    for &(_, dst) in pipes {
+        let cell = &mut core.core[ops.b];
+        cell.set_value(dst, wrap(cell.value(dst) as usize + m - 1, m) as u32);
+    }
+
+    // the test looks at the copy, decremented the same way
+    let non_zero = pipes.iter().any(|&(_, dst)| ops.irb.value(dst) != 1);
+    jump_if(core, ops, non_zero)
+}
+
+#[inline(always)]
+fn skip_if(core: &CoreRuntime, ops: &Operands, skip: bool) -> Flow {
+    let next = next(core, ops.pc);
+
+    if skip {
+        Flow::Continue(wrap(next + 1, core.core_size))
+    } else {
+        Flow::Continue(next)
+    }
+}
+
+fn equal(ops: &Operands, pipes: &[(usize, usize)]) -> bool {
+    pipes
+        .iter()
+        .all(|&(src, dst)| ops.ira.value(src) == ops.irb.value(dst))
+}
+
+#[inline(always)]
+fn seq(core: &mut CoreRuntime, ops: &Operands, pipes: &[(usize, usize)]) -> Flow {
+    skip_if(core, ops, equal(ops, pipes))
+}
+
+#[inline(always)]
+fn sne(core: &mut CoreRuntime, ops: &Operands, pipes: &[(usize, usize)]) -> Flow {
+    skip_if(core, ops, !equal(ops, pipes))
+}
+
+#[inline(always)]
+fn slt(core: &mut CoreRuntime, ops: &Operands, pipes: &[(usize, usize)]) -> Flow {
+    let lower = pipes
+        .iter()
+        .all(|&(src, dst)| ops.ira.value(src) < ops.irb.value(dst));
+
+    skip_if(core, ops, lower)
+}
+
+fn dat(_: &mut CoreRuntime, _: &Operands) -> Flow {
+    Flow::Die
+}
+
+fn mov_i(core: &mut CoreRuntime, ops: &Operands) -> Flow {
+    core.core[ops.b] = ops.ira;
+    Flow::Continue(next(core, ops.pc))
+}
+
+fn jmp(_: &mut CoreRuntime, ops: &Operands) -> Flow {
+    Flow::Continue(ops.a)
+}
+
+fn spl(_: &mut CoreRuntime, _: &Operands) -> Flow {
+    Flow::Split
+}
+
+fn seq_i(core: &mut CoreRuntime, ops: &Operands) -> Flow {
+    skip_if(core, ops, ops.ira.same_instruction(&ops.irb))
+}
+
+fn sne_i(core: &mut CoreRuntime, ops: &Operands) -> Flow {
+    skip_if(core, ops, !ops.ira.same_instruction(&ops.irb))
+}
+
+fn nop(core: &mut CoreRuntime, ops: &Operands) -> Flow {
+    Flow::Continue(next(core, ops.pc))
+}
+
+macro_rules! handlers {
+    ($($name:ident => $body:ident($($arg:expr),*);)*) => {
+        $(
+            fn $name(core: &mut CoreRuntime, ops: &Operands) -> Flow {
+                $body(core, ops, $($arg),*)
+            }
+        )*
+    };
+}
+
+handlers! {
+    mov_a => mov(A);
+    mov_b => mov(B);
+    mov_ab => mov(AB);
+    mov_ba => mov(BA);
+    mov_f => mov(F);
+    mov_x => mov(X);
+
+    add_a => arithmetic(A, add);
+    add_b => arithmetic(B, add);
+    add_ab => arithmetic(AB, add);
+    add_ba => arithmetic(BA, add);
+    add_f => arithmetic(F, add);
+    add_x => arithmetic(X, add);
+
+    sub_a => arithmetic(A, sub);
+    sub_b => arithmetic(B, sub);
+    sub_ab => arithmetic(AB, sub);
+    sub_ba => arithmetic(BA, sub);
+    sub_f => arithmetic(F, sub);
+    sub_x => arithmetic(X, sub);
+
+    mul_a => arithmetic(A, mul);
+    mul_b => arithmetic(B, mul);
+    mul_ab => arithmetic(AB, mul);
+    mul_ba => arithmetic(BA, mul);
+    mul_f => arithmetic(F, mul);
+    mul_x => arithmetic(X, mul);
+
+    div_a => arithmetic(A, div);
+    div_b => arithmetic(B, div);
+    div_ab => arithmetic(AB, div);
+    div_ba => arithmetic(BA, div);
+    div_f => arithmetic(F, div);
+    div_x => arithmetic(X, div);
+
+    mod_a => arithmetic(A, rem);
+    mod_b => arithmetic(B, rem);
+    mod_ab => arithmetic(AB, rem);
+    mod_ba => arithmetic(BA, rem);
+    mod_f => arithmetic(F, rem);
+    mod_x => arithmetic(X, rem);
+
+    jmz_a => jmz(A);
+    jmz_b => jmz(B);
+    jmz_f => jmz(F);
+
+    jmn_a => jmn(A);
+    jmn_b => jmn(B);
+    jmn_f => jmn(F);
+
+    djn_a => djn(A);
+    djn_b => djn(B);
+    djn_f => djn(F);
+
+    seq_a => seq(A);
+    seq_b => seq(B);
+    seq_ab => seq(AB);
+    seq_ba => seq(BA);
+    seq_f => seq(F);
+    seq_x => seq(X);
+
+    sne_a => sne(A);
+    sne_b => sne(B);
+    sne_ab => sne(AB);
+    sne_ba => sne(BA);
+    sne_f => sne(F);
+    sne_x => sne(X);
+
+    slt_a => slt(A);
+    slt_b => slt(B);
+    slt_ab => slt(AB);
+    slt_ba => slt(BA);
+    slt_f => slt(F);
+    slt_x => slt(X);
+}
+
+// Rows follow `OpCode`, columns `OpModifier` (A, B, AB, BA, F, X, I). The
+// arithmetic and SLT .I forms behave as .F; the jumps only ever test the
+// B operand, so .AB is .B, .BA is .A and .X and .I are .F.
+static HANDLERS: [[Handler; MODIFIERS]; 17] = [
+    [dat; MODIFIERS],
+    [mov_a, mov_b, mov_ab, mov_ba, mov_f, mov_x, mov_i],
+    [add_a, add_b, add_ab, add_ba, add_f, add_x, add_f],
+    [sub_a, sub_b, sub_ab, sub_ba, sub_f, sub_x, sub_f],
+    [mul_a, mul_b, mul_ab, mul_ba, mul_f, mul_x, mul_f],
+    [div_a, div_b, div_ab, div_ba, div_f, div_x, div_f],
+    [mod_a, mod_b, mod_ab, mod_ba, mod_f, mod_x, mod_f],
+    [jmp; MODIFIERS],
+    [jmz_a, jmz_b, jmz_b, jmz_a, jmz_f, jmz_f, jmz_f],
+    [jmn_a, jmn_b, jmn_b, jmn_a, jmn_f, jmn_f, jmn_f],
+    [djn_a, djn_b, djn_b, djn_a, djn_f, djn_f, djn_f],
+    [spl; MODIFIERS],
+    [seq_a, seq_b, seq_ab, seq_ba, seq_f, seq_x, seq_i],
+    [seq_a, seq_b, seq_ab, seq_ba, seq_f, seq_x, seq_i],
+    [sne_a, sne_b, sne_ab, sne_ba, sne_f, sne_x, sne_i],
+    [slt_a, slt_b, slt_ab, slt_ba, slt_f, slt_x, slt_f],
+    [nop; MODIFIERS],
+];
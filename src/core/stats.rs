@@ -0,0 +1,89 @@
+/// What a warrior did during the rounds it played, collected when the core
+/// is asked to, see `CoreRuntime::collect_stats`.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WarriorStats {
+    /// Rounds the counts below are summed over.
+    pub rounds: usize,
+    /// Instructions executed.
+    pub cycles: usize,
+    /// Processes started by SPL.
+    pub spawned: usize,
+    /// Cell writes, increments and decrements included.
+    pub writes: usize,
+    /// Distinct cells written, counted once per round.
+    pub cells_written: usize,
+    /// Instructions executed outside the cells the warrior was loaded into.
+    pub away: usize,
+}
+
+impl WarriorStats {
+    pub fn add(&mut self, other: &WarriorStats) {
+        self.rounds += other.rounds;
+        self.cycles += other.cycles;
+        self.spawned += other.spawned;
+        self.writes += other.writes;
+        self.cells_written += other.cells_written;
+        self.away += other.away;
+    }
+}
+
+/// Statistics of the round being played, by deploy index.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Collector {
+    pub stats: Vec<WarriorStats>,
+    /// Load address and length of every warrior.
+    bodies: Vec<(usize, usize)>,
+    /// Cells every warrior has written this round.
+    written: Vec<Vec<bool>>,
+    core_size: usize,
+}
+
+impl Collector {
+    pub fn reset(&mut self, bodies: impl Iterator<Item = (usize, usize)>, core_size: usize) {
+        self.bodies.clear();
+        self.bodies.extend(bodies);
+        self.core_size = core_size;
+
+        let n = self.bodies.len();
+        self.stats.clear();
+        self.stats.resize(
+            n,
+            WarriorStats {
+                rounds: 1,
+                ..Default::default()
+            },
+        );
+
+        self.written.resize(n, vec![]);
+        for written in self.written.iter_mut() {
+            written.clear();
+            written.resize(core_size, false);
+        }
+    }
+
+    pub fn executed(&mut self, warrior: usize, pc: usize) {
+        let (start, len) = self.bodies[warrior];
+        let stats = &mut self.stats[warrior];
+
+        stats.cycles += 1;
+        if (pc + self.core_size - start) % self.core_size >= len {
+            stats.away += 1;
+        }
+    }
+
+    pub fn wrote(&mut self, warrior: usize, cell: usize) {
+        let stats = &mut self.stats[warrior];
+        stats.writes += 1;
+
+        if !self.written[warrior][cell] {
+            self.written[warrior][cell] = true;
+            stats.cells_written += 1;
+        }
+    }
+
+    pub fn spawned(&mut self, warrior: usize) {
+        self.stats[warrior].spawned += 1;
+    }
+}
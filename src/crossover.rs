@@ -0,0 +1,186 @@
+use core::fmt;
+
+use rand::Rng;
+
+use crate::{battle::BattleConfig, warrior::Warrior};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CrossoverOperator {
+    /// The first parent up to a cut, the second from there on.
+    OnePoint,
+    /// The second parent between two cuts, the first everywhere else.
+    TwoPoint,
+    /// Every instruction from either parent at random.
+    Uniform,
+}
+
+impl CrossoverOperator {
+    pub const ALL: [CrossoverOperator; 3] = [
+        CrossoverOperator::OnePoint,
+        CrossoverOperator::TwoPoint,
+        CrossoverOperator::Uniform,
+    ];
+}
+
+impl fmt::Display for CrossoverOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                CrossoverOperator::OnePoint => "one_point",
+                CrossoverOperator::TwoPoint => "two_point",
+                CrossoverOperator::Uniform => "uniform",
+            }
+        )
+    }
+}
+
+/// Relative weights of the crossover operators, an operator weighted 0 is
+/// never applied.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CrossoverConfig {
+    pub one_point: f64,
+    pub two_point: f64,
+    pub uniform: f64,
+    /// Line both bodies up on their `org` instead of their first
+    /// instruction, so code keeps its offset from where execution starts.
+    pub align_org: bool,
+    /// Offspring are cut down to this many instructions.
+    pub max_length: usize,
+}
+
+impl CrossoverConfig {
+    pub fn new(battle: &BattleConfig) -> Self {
+        Self {
+            one_point: 1.,
+            two_point: 1.,
+            uniform: 1.,
+            align_org: true,
+            max_length: battle.max_length,
+        }
+    }
+
+    pub fn weight(&self, operator: CrossoverOperator) -> f64 {
+        match operator {
+            CrossoverOperator::OnePoint => self.one_point,
+            CrossoverOperator::TwoPoint => self.two_point,
+            CrossoverOperator::Uniform => self.uniform,
+        }
+    }
+
+    pub fn set_weight(&mut self, operator: CrossoverOperator, weight: f64) {
+        match operator {
+            CrossoverOperator::OnePoint => self.one_point = weight,
+            CrossoverOperator::TwoPoint => self.two_point = weight,
+            CrossoverOperator::Uniform => self.uniform = weight,
+        }
+    }
+
+    /// Recombines `a` and `b` with an operator picked by weight, returning
+    /// the offspring and the operator, or `None` if every weight is 0. The
+    /// offspring is named after and starts where `a` does.
+    pub fn crossover(
+        &self,
+        a: &Warrior,
+        b: &Warrior,
+        rng: &mut impl Rng,
+    ) -> Option<(Warrior, CrossoverOperator)> {
+        let total: f64 = CrossoverOperator::ALL
+            .iter()
+            .map(|op| self.weight(*op))
+            .sum();
+        if total <= 0. {
+            return None;
+        }
+
+        let mut pick = rng.gen_range(0. ..total);
+        let operator = CrossoverOperator::ALL
+            .into_iter()
+            .find(|op| {
+                let found = pick < self.weight(*op);
+                pick -= self.weight(*op);
+                found
+            })
+            .unwrap_or(CrossoverOperator::Uniform);
+
+        Some((self.apply(a, b, operator, rng), operator))
+    }
+
+    /// Recombines `a` and `b` with the given operator.
+    pub fn apply(
+        &self,
+        a: &Warrior,
+        b: &Warrior,
+        operator: CrossoverOperator,
+        rng: &mut impl Rng,
+    ) -> Warrior {
+        // instructions are placed by offset from their parent's shift, the
+        // org when aligning and the first instruction otherwise
+        let shift = |w: &Warrior| if self.align_org { w.org as isize } else { 0 };
+        let (shift_a, shift_b) = (shift(a), shift(b));
+        let start = (-shift_a).min(-shift_b);
+        let end = (a.body.len() as isize - shift_a).max(b.body.len() as isize - shift_b);
+        let span = (end - start).max(0) as usize;
+
+        let from_a: Vec<bool> = match operator {
+            CrossoverOperator::OnePoint => {
+                let cut = rng.gen_range(0..=span);
+                (0..span).map(|i| i < cut).collect()
+            }
+            CrossoverOperator::TwoPoint => {
+                let (x, y) = (rng.gen_range(0..=span), rng.gen_range(0..=span));
+                let (first, second) = (x.min(y), x.max(y));
+                (0..span).map(|i| i < first || i >= second).collect()
+            }
+            CrossoverOperator::Uniform => (0..span).map(|_| rng.gen_bool(0.5)).collect(),
+        };
+
+        let cell = |parent: &Warrior, shift: isize, offset: isize| {
+            usize::try_from(offset + shift)
+                .ok()
+                .and_then(|index| parent.body.get(index).copied())
+        };
+
+        // an offset the picked parent doesn't reach is dropped at either end
+        // of the offspring, and taken from the other parent in between so
+        // nothing past it moves
+        let picked: Vec<_> = from_a
+            .iter()
+            .enumerate()
+            .map(|(i, from_a)| {
+                let offset = start + i as isize;
+                if *from_a {
+                    cell(a, shift_a, offset)
+                } else {
+                    cell(b, shift_b, offset)
+                }
+            })
+            .collect();
+        let first = picked.iter().position(Option::is_some).unwrap_or(span);
+        let last = picked
+            .iter()
+            .rposition(Option::is_some)
+            .map_or(first, |i| i + 1);
+
+        let mut offspring = a.clone();
+        offspring.body = (first..last)
+            .map(|i| {
+                let offset = start + i as isize;
+                picked[i]
+                    .or_else(|| cell(a, shift_a, offset))
+                    .or_else(|| cell(b, shift_b, offset))
+                    .unwrap()
+            })
+            .collect();
+
+        let org_offset = a.org as isize - shift_a;
+        offspring.org = (org_offset - start - first as isize).max(0) as usize;
+
+        offspring.body.truncate(self.max_length);
+        offspring.org = offspring.org.min(offspring.body.len().saturating_sub(1));
+        offspring
+    }
+}
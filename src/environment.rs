@@ -0,0 +1,161 @@
+use std::fs;
+
+use crate::{
+    battle::BattleConfig,
+    core::CoreConfig,
+    settings::{self, Table},
+};
+
+/// Names of the built in environments, see `Environment::preset`.
+pub const PRESETS: [&str; 5] = ["94nop", "94x", "tiny", "nano", "lp"];
+
+const KEYS: [&str; 11] = [
+    "preset",
+    "standard",
+    "core_size",
+    "max_cycles",
+    "max_processes",
+    "max_length",
+    "min_distance",
+    "read_limit",
+    "write_limit",
+    "pspace_size",
+    "rounds",
+];
+
+/// Everything about the world warriors fight in, the way the King of the
+/// Hill standards describe it. Written as TOML:
+///
+/// ```toml
+/// preset = "tiny"     # what the other keys change, 94nop if not given
+/// standard = "94"
+/// core_size = 800     # read_limit, write_limit and pspace_size follow it
+/// max_cycles = 8000   # unless they're given too
+/// max_processes = 800
+/// max_length = 20
+/// min_distance = 20
+/// read_limit = 800
+/// write_limit = 800
+/// pspace_size = 50
+/// rounds = 250
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Environment {
+    /// The preset or file the environment came from.
+    pub name: String,
+    /// Instruction set, "94" for ICWS '94, the only one the core runs.
+    pub standard: String,
+    pub battle: BattleConfig,
+    /// How far away from itself an instruction may read, the core size for
+    /// no limit.
+    pub read_limit: usize,
+    /// How far away from itself an instruction may write.
+    pub write_limit: usize,
+    /// Cells of private storage every warrior gets. There's no LDP or STP
+    /// yet, so nothing uses it.
+    pub pspace_size: usize,
+}
+
+impl Environment {
+    /// One of `PRESETS`, played over 250 rounds with p-space a sixteenth of
+    /// the core, as pMARS sizes it.
+    pub fn preset(name: &str) -> Option<Self> {
+        let battle = BattleConfig::preset(name)?;
+        Some(Self {
+            name: name.into(),
+            standard: "94".into(),
+            read_limit: battle.core_size,
+            write_limit: battle.core_size,
+            pspace_size: battle.core_size / 16,
+            battle,
+        })
+    }
+
+    /// A preset by name, or else the environment file at `name`.
+    pub fn find(name: &str) -> Result<Self, String> {
+        match Self::preset(name) {
+            Some(environment) => Ok(environment),
+            None if name.ends_with(".toml") => Self::load(name),
+            None => Err(format!(
+                "unknown environment {name}, expected a .toml file or one of {}",
+                PRESETS.join(", ")
+            )),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source = fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+        let table = settings::parse(&source).map_err(|err| format!("{path}: {err}"))?;
+        let mut environment = Self::from_table(&table).map_err(|err| format!("{path}: {err}"))?;
+        environment.name = path.into();
+        Ok(environment)
+    }
+
+    pub fn from_table(table: &Table) -> Result<Self, String> {
+        table.expect_only(&KEYS)?;
+
+        let preset = table.string("preset")?.unwrap_or("94nop");
+        let mut environment = Self::preset(preset).ok_or_else(|| {
+            format!(
+                "unknown preset {preset}, expected one of {}",
+                PRESETS.join(", ")
+            )
+        })?;
+
+        if let Some(core_size) = table.usize("core_size")? {
+            environment.battle.core_size = core_size;
+            environment.read_limit = core_size;
+            environment.write_limit = core_size;
+            environment.pspace_size = core_size / 16;
+        }
+        for (key, field) in [
+            ("max_cycles", &mut environment.battle.max_cycles),
+            ("max_processes", &mut environment.battle.max_processes),
+            ("max_length", &mut environment.battle.max_length),
+            ("min_distance", &mut environment.battle.min_distance),
+            ("read_limit", &mut environment.read_limit),
+            ("write_limit", &mut environment.write_limit),
+            ("pspace_size", &mut environment.pspace_size),
+            ("rounds", &mut environment.battle.rounds),
+        ] {
+            if let Some(value) = table.usize(key)? {
+                *field = value;
+            }
+        }
+        if let Some(standard) = table.string("standard")? {
+            environment.standard = standard.into();
+        }
+
+        environment.validate()?;
+        Ok(environment)
+    }
+
+    /// Fails on settings the core can't play by.
+    pub fn validate(&self) -> Result<(), String> {
+        let core_size = self.battle.core_size;
+        if core_size == 0 {
+            return Err("the core needs at least one cell".into());
+        }
+        if self.standard != "94" {
+            return Err(format!(
+                "only the '94 standard is supported, not {}",
+                self.standard
+            ));
+        }
+        // the core has no folding of addresses, every cell is in reach
+        if self.read_limit != core_size || self.write_limit != core_size {
+            return Err(format!(
+                "read and write limits other than the core size ({core_size}) aren't supported"
+            ));
+        }
+        if self.battle.max_length == 0 || self.battle.max_length > core_size {
+            return Err(format!("max_length should be between 1 and {core_size}"));
+        }
+        Ok(())
+    }
+
+    pub fn core_config(&self) -> CoreConfig {
+        self.battle.core_config()
+    }
+}
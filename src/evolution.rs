@@ -0,0 +1,784 @@
+use std::{
+    collections::HashSet,
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+
+use crate::{
+    batch::Batch,
+    battle::BattleConfig,
+    benchmark::Benchmark,
+    crossover::CrossoverConfig,
+    hall_of_fame::{HallOfFame, HallOfFameConfig},
+    instruction::op_code::OpCode,
+    mutation::MutationConfig,
+    warrior::{Lineage, Warrior},
+};
+
+/// How parents are picked out of a scored population.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Selection {
+    /// Uniformly among the best `fraction` of the population.
+    Truncation(f64),
+    /// The best of `size` warriors drawn at random.
+    Tournament(usize),
+    /// With probability proportional to score.
+    Roulette,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EvolutionConfig {
+    pub population: usize,
+    pub generations: usize,
+    /// Length of the warriors the first generation is made of.
+    pub warrior_length: usize,
+    pub selection: Selection,
+    /// Best warriors carried over to the next generation untouched.
+    pub elitism: usize,
+    /// Chance an offspring is mutated.
+    pub mutation_rate: f64,
+    /// Which mutations are applied and how often.
+    pub mutation: MutationConfig,
+    /// Chance an offspring is recombined from two parents instead of copied
+    /// from one.
+    pub crossover_rate: f64,
+    /// Which crossovers are applied and how often.
+    pub crossover: CrossoverConfig,
+    /// Rules every warrior is evaluated under, against every other one.
+    pub battle: BattleConfig,
+    /// Scores warriors against a fixed set instead of each other.
+    pub benchmark: Option<Benchmark>,
+    /// With a benchmark, how much the round robin within the population
+    /// adds to the benchmark score. Both count in benchmark units, 0 skips
+    /// the round robin altogether.
+    pub pool_weight: f64,
+    /// Keeps the champion of every generation for later ones to beat.
+    pub hall_of_fame: Option<HallOfFameConfig>,
+    pub seed: Option<u64>,
+    /// Threads matches are played on, every CPU if not given.
+    pub threads: Option<usize>,
+    /// Directory every evaluated generation is saved to, see
+    /// `Evolution::resume`.
+    pub checkpoint: Option<PathBuf>,
+    /// File every generation's report is appended to, as JSON lines if it
+    /// ends in `.jsonl` and as CSV otherwise.
+    pub statistics: Option<PathBuf>,
+    /// CSV file every warrior's `Lineage` is appended to as it's born.
+    pub lineage: Option<PathBuf>,
+}
+
+impl EvolutionConfig {
+    pub fn new(battle: BattleConfig) -> Self {
+        Self {
+            population: 20,
+            generations: 100,
+            warrior_length: battle.max_length.min(20),
+            selection: Selection::Tournament(3),
+            elitism: 2,
+            mutation_rate: 0.9,
+            mutation: MutationConfig::new(&battle),
+            crossover_rate: 0.3,
+            crossover: CrossoverConfig::new(&battle),
+            battle,
+            benchmark: None,
+            pool_weight: 0.,
+            hall_of_fame: None,
+            seed: None,
+            threads: None,
+            checkpoint: None,
+            statistics: None,
+            lineage: None,
+        }
+    }
+
+    /// Fails on settings no population can be started from.
+    pub fn validate(&self) -> Result<(), String> {
+        let max_length = self.battle.max_length;
+        if self.warrior_length == 0 || self.warrior_length > max_length {
+            return Err(format!(
+                "warrior_length should be between 1 and {max_length}"
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// What happened in one generation, as seen right after evaluating it.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GenerationReport {
+    pub generation: usize,
+    pub best_score: f64,
+    pub mean_score: f64,
+    pub median_score: f64,
+    /// Fraction of the warriors that aren't a copy of another one.
+    pub diversity: f64,
+    pub mean_length: f64,
+    /// Instructions of the whole population by opcode, in `OpCode::ALL`
+    /// order.
+    pub opcodes: Vec<usize>,
+    pub best: Warrior,
+}
+
+impl GenerationReport {
+    pub fn new(generation: usize, population: &[Warrior], scores: &[f64]) -> Self {
+        let n = population.len() as f64;
+
+        let mut sorted = scores.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let median_score = match sorted.len() {
+            0 => 0.,
+            len if len % 2 == 1 => sorted[len / 2],
+            len => (sorted[len / 2 - 1] + sorted[len / 2]) / 2.,
+        };
+
+        // told apart by their redcode, which is all a checkpoint keeps
+        let distinct: HashSet<String> = population
+            .iter()
+            .map(|w| {
+                let body: Vec<String> = w.body.iter().map(|i| i.to_string()).collect();
+                format!("{} {}", w.org, body.join("\n"))
+            })
+            .collect();
+
+        let mut opcodes = vec![0; OpCode::ALL.len()];
+        for instruction in population.iter().flat_map(|w| w.body.iter()) {
+            opcodes[instruction.code as usize] += 1;
+        }
+
+        let best = (0..scores.len())
+            .max_by(|a, b| scores[*a].total_cmp(&scores[*b]).then(b.cmp(a)))
+            .unwrap_or(0);
+
+        Self {
+            generation,
+            best_score: scores.get(best).copied().unwrap_or(0.),
+            mean_score: scores.iter().sum::<f64>() / scores.len().max(1) as f64,
+            median_score,
+            diversity: distinct.len() as f64 / n.max(1.),
+            mean_length: population.iter().map(|w| w.body.len()).sum::<usize>() as f64 / n.max(1.),
+            opcodes,
+            best: population[best].clone(),
+        }
+    }
+
+    pub fn csv_header() -> String {
+        let opcodes: Vec<String> = OpCode::ALL.iter().map(|op| op.to_string()).collect();
+        format!(
+            "generation,best_score,mean_score,median_score,diversity,mean_length,best_id,{}",
+            opcodes.join(",")
+        )
+    }
+
+    /// The generation of a CSV or JSON statistics row, none for a header.
+    pub fn row_generation(row: &str) -> Option<usize> {
+        let row = row.strip_prefix("{\"generation\":").unwrap_or(row);
+        let end = row.find(',').unwrap_or(row.len());
+        row[..end].parse().ok()
+    }
+
+    pub fn to_csv(&self) -> String {
+        let opcodes: Vec<String> = self.opcodes.iter().map(|n| n.to_string()).collect();
+        format!(
+            "{},{},{},{},{},{},{},{}",
+            self.generation,
+            self.best_score,
+            self.mean_score,
+            self.median_score,
+            self.diversity,
+            self.mean_length,
+            self.best.lineage.id,
+            opcodes.join(",")
+        )
+    }
+
+    pub fn to_json(&self) -> String {
+        let opcodes: Vec<String> = OpCode::ALL
+            .iter()
+            .zip(&self.opcodes)
+            .map(|(op, n)| format!("\"{op}\":{n}"))
+            .collect();
+        format!(
+            "{{\"generation\":{},\"best_score\":{},\"mean_score\":{},\"median_score\":{},\"diversity\":{},\"mean_length\":{},\"best_id\":{},\"opcodes\":{{{}}}}}",
+            self.generation,
+            self.best_score,
+            self.mean_score,
+            self.median_score,
+            self.diversity,
+            self.mean_length,
+            self.best.lineage.id,
+            opcodes.join(",")
+        )
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Evolution {
+    pub config: EvolutionConfig,
+    pub population: Vec<Warrior>,
+    /// Scores of `population` once evaluated, in the same order.
+    pub scores: Vec<f64>,
+    pub generation: usize,
+    pub hall_of_fame: Option<HallOfFame>,
+    /// Lineage id the next warrior born gets.
+    next_id: u64,
+    // the generator behind StdRng, which can't be saved and restored
+    rng: ChaCha12Rng,
+}
+
+/*
+    A checkpoint directory holds one directory per generation, written
+    right after the generation is evaluated:
+
+        generation_<n>/state        generation, next lineage id and RNG seed, stream and
+                                    word position
+        generation_<n>/scores       one score per warrior, in population order
+        generation_<n>/<i>.red      the i-th warrior, as a load file
+        generation_<n>/hall_of_fame/<i>.red
+                                    the i-th champion, oldest first
+
+    `state` is written last, a directory without one is incomplete.
+*/
+
+/// The generator a run starts from, seeded from the OS without a seed.
+fn seeded(seed: Option<u64>) -> ChaCha12Rng {
+    match seed {
+        Some(seed) => ChaCha12Rng::seed_from_u64(seed),
+        None => ChaCha12Rng::from_entropy(),
+    }
+}
+
+fn io_err(path: &Path) -> impl Fn(std::io::Error) -> String + '_ {
+    move |err| format!("{}: {err}", path.display())
+}
+
+/// Appends `lines` to `path`, starting it with `header` if it's empty.
+fn append(path: &Path, header: &str, lines: &[String]) -> Result<(), String> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(io_err(path))?;
+    let new = file.metadata().map_err(io_err(path))?.len() == 0;
+
+    let mut contents = String::new();
+    if new && !header.is_empty() {
+        contents.push_str(header);
+        contents.push('\n');
+    }
+    for line in lines {
+        contents.push_str(line);
+        contents.push('\n');
+    }
+
+    file.write_all(contents.as_bytes()).map_err(io_err(path))
+}
+
+/// Rewrites the log file at `path`, if there is one, with only the rows
+/// of the generations `keep` accepts and the header they came under.
+/// Empties it when no row is kept, so that `append` starts it again.
+fn rewind(
+    path: &Path,
+    generation_of: impl Fn(&str) -> Option<usize>,
+    keep: impl Fn(usize) -> bool,
+) -> Result<(), String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(io_err(path)(err)),
+    };
+
+    let mut lines = contents.lines().peekable();
+    let header = lines.next_if(|line| generation_of(line).is_none());
+    let rows: Vec<&str> = lines
+        .filter(|row| generation_of(row).is_some_and(&keep))
+        .collect();
+
+    let mut kept = String::new();
+    if !rows.is_empty() {
+        for line in header.into_iter().chain(rows) {
+            kept.push_str(line);
+            kept.push('\n');
+        }
+    }
+    fs::write(path, kept).map_err(io_err(path))
+}
+
+/// Reads every record of a lineage file, see `Lineage::family_tree`.
+pub fn read_lineage(path: &Path) -> Result<Vec<Lineage>, String> {
+    fs::read_to_string(path)
+        .map_err(io_err(path))?
+        .lines()
+        .skip(1)
+        .map(Lineage::parse_csv)
+        .collect()
+}
+
+impl Evolution {
+    /// Starts from a population of random warriors.
+    pub fn new(config: EvolutionConfig) -> Result<Self, String> {
+        config.validate()?;
+        let mut rng = seeded(config.seed);
+
+        let population = (0..config.population)
+            .map(|_| {
+                Warrior::random_create_with_rng(
+                    config.warrior_length,
+                    config.battle.core_size,
+                    &mut rng,
+                )
+            })
+            .collect();
+
+        Self::start(config, population, rng)
+    }
+
+    /// Starts from the given warriors instead of random ones.
+    pub fn with_population(
+        mut config: EvolutionConfig,
+        population: Vec<Warrior>,
+    ) -> Result<Self, String> {
+        config.population = population.len();
+        let rng = seeded(config.seed);
+        Self::start(config, population, rng)
+    }
+
+    fn start(
+        config: EvolutionConfig,
+        population: Vec<Warrior>,
+        rng: ChaCha12Rng,
+    ) -> Result<Self, String> {
+        let mut evolution = Self {
+            hall_of_fame: config.hall_of_fame.clone().map(HallOfFame::new),
+            config,
+            population,
+            scores: vec![],
+            generation: 0,
+            next_id: 0,
+            rng,
+        };
+        evolution.name_founders();
+        evolution.rewind_logs()?;
+        Ok(evolution)
+    }
+
+    /// Numbers lineages from `id` on, founders included, so that runs
+    /// sharing warriors keep apart ones they each gave the same number.
+    pub(crate) fn number_from(&mut self, id: u64) {
+        self.next_id = id;
+        self.name_founders();
+    }
+
+    /// Puts a copy of `migrant` from another run in place of the warrior at
+    /// `slot`, with a lineage of its own naming the migrant as its parent.
+    pub(crate) fn adopt(&mut self, slot: usize, migrant: &Warrior) {
+        let mut warrior = migrant.clone();
+        warrior.lineage = Lineage {
+            id: self.next_id,
+            generation: self.generation,
+            parents: vec![migrant.lineage.id],
+            operators: vec!["migration".into()],
+        };
+        self.next_id += 1;
+        self.population[slot] = warrior;
+    }
+
+    /// Drops the rows of the statistics and lineage files that the run
+    /// writes from here on, which for a new run is all of them. A resumed
+    /// generation was evaluated, so its births are logged already but not
+    /// its report.
+    fn rewind_logs(&self) -> Result<(), String> {
+        let generation = self.generation;
+        let evaluated = !self.population.is_empty() && self.scores.len() == self.population.len();
+
+        if let Some(path) = &self.config.statistics {
+            rewind(path, GenerationReport::row_generation, |g| g < generation)?;
+        }
+        if let Some(path) = &self.config.lineage {
+            let born = |row: &str| Lineage::parse_csv(row).ok().map(|l| l.generation);
+            rewind(path, born, |g| {
+                g < generation || (evaluated && g == generation)
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Gives every warrior of the first generation a lineage of its own.
+    fn name_founders(&mut self) {
+        for warrior in self.population.iter_mut() {
+            warrior.lineage = Lineage {
+                id: self.next_id,
+                ..Default::default()
+            };
+            self.next_id += 1;
+        }
+    }
+
+    /// Picks a run up from the latest generation saved in `dir`. With the
+    /// same config it goes on exactly as the run that saved it would have.
+    pub fn resume(config: EvolutionConfig, dir: &Path) -> Result<Self, String> {
+        config.validate()?;
+        let latest = fs::read_dir(dir)
+            .map_err(io_err(dir))?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().join("state").exists())
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let generation = name.strip_prefix("generation_")?.parse::<usize>().ok()?;
+                Some((generation, entry.path()))
+            })
+            .max_by_key(|(generation, _)| *generation)
+            .map(|(_, path)| path)
+            .ok_or(format!("{} holds no checkpoint", dir.display()))?;
+
+        let read = |name: &str| {
+            let path = latest.join(name);
+            fs::read_to_string(&path).map_err(io_err(&path))
+        };
+        let bad = |what: &str| format!("{}: bad {what}", latest.display());
+
+        let mut generation = None;
+        let mut next_id = None;
+        let mut seed = None;
+        let mut stream = None;
+        let mut word_pos = None;
+        for line in read("state")?.lines() {
+            let Some((key, value)) = line.split_once(' ') else {
+                continue;
+            };
+
+            match key {
+                "generation" => generation = value.parse::<usize>().ok(),
+                "next_id" => next_id = value.parse::<u64>().ok(),
+                "seed" => seed = parse_seed(value),
+                "stream" => stream = value.parse::<u64>().ok(),
+                "word_pos" => word_pos = value.parse::<u128>().ok(),
+                _ => return Err(format!("unknown checkpoint state {key}")),
+            }
+        }
+
+        let mut rng = ChaCha12Rng::from_seed(seed.ok_or(bad("seed"))?);
+        rng.set_stream(stream.ok_or(bad("stream"))?);
+        rng.set_word_pos(word_pos.ok_or(bad("word_pos"))?);
+
+        let scores = read("scores")?
+            .lines()
+            .map(|s| s.parse::<f64>().map_err(|_| bad("score")))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let load = |name: &str| {
+            let path = latest.join(name);
+            let mut warrior = Warrior::load(&path.to_string_lossy(), config.battle.core_size)?;
+            let source = read(name)?;
+            warrior.lineage = Lineage::from_comments(&source).unwrap_or_default();
+            Ok::<_, String>((warrior, source))
+        };
+        let population = (0..scores.len())
+            .map(|i| Ok(load(&format!("{i}.red"))?.0))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let mut hall_of_fame = config.hall_of_fame.clone().map(HallOfFame::new);
+        if let Some(hall_of_fame) = &mut hall_of_fame {
+            for i in 0.. {
+                let name = format!("hall_of_fame/{i}.red");
+                if !latest.join(&name).exists() {
+                    break;
+                }
+
+                let (champion, source) = load(&name)?;
+                let generation = source
+                    .lines()
+                    .find_map(|line| line.strip_prefix(";generation "))
+                    .and_then(|g| g.trim().parse().ok())
+                    .ok_or(bad("champion generation"))?;
+                hall_of_fame.add(generation, champion);
+            }
+        }
+
+        let evolution = Self {
+            config: EvolutionConfig {
+                population: population.len(),
+                ..config
+            },
+            population,
+            scores,
+            generation: generation.ok_or(bad("generation"))?,
+            hall_of_fame,
+            next_id: next_id.ok_or(bad("next_id"))?,
+            rng,
+        };
+        evolution.rewind_logs()?;
+        Ok(evolution)
+    }
+
+    /// Saves the evaluated generation to `dir`, see `resume`.
+    pub fn save_checkpoint(&self, dir: &Path) -> Result<(), String> {
+        let dir = dir.join(format!("generation_{:06}", self.generation));
+        fs::create_dir_all(&dir).map_err(io_err(&dir))?;
+
+        let write = |name: &str, contents: String| {
+            let path = dir.join(name);
+            fs::write(&path, contents).map_err(io_err(&path))
+        };
+
+        for (i, (warrior, score)) in self.population.iter().zip(&self.scores).enumerate() {
+            write(
+                &format!("{i}.red"),
+                format!(
+                    ";generation {}\n;score {score}\n{}{}",
+                    self.generation,
+                    warrior.lineage.to_comments(),
+                    warrior.to_load_file()
+                ),
+            )?;
+        }
+
+        let scores: Vec<String> = self.scores.iter().map(|s| format!("{s}\n")).collect();
+        write("scores", scores.concat())?;
+
+        if let Some(hall_of_fame) = &self.hall_of_fame {
+            let archive = dir.join("hall_of_fame");
+            fs::create_dir_all(&archive).map_err(io_err(&archive))?;
+
+            for (i, champion) in hall_of_fame.champions.iter().enumerate() {
+                write(
+                    &format!("hall_of_fame/{i}.red"),
+                    format!(
+                        ";generation {}\n{}{}",
+                        champion.generation,
+                        champion.warrior.lineage.to_comments(),
+                        champion.warrior.to_load_file()
+                    ),
+                )?;
+            }
+        }
+
+        let seed: String = self
+            .rng
+            .get_seed()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        write(
+            "state",
+            format!(
+                "generation {}\nnext_id {}\nseed {seed}\nstream {}\nword_pos {}\n",
+                self.generation,
+                self.next_id,
+                self.rng.get_stream(),
+                self.rng.get_word_pos()
+            ),
+        )
+    }
+
+    /// Scores every warrior, by the points it earns per round in a round
+    /// robin against the rest of the population or, given a benchmark, by
+    /// its benchmark score. Both are times 100, so 300 is winning every
+    /// round. Plus whatever it earns against the hall of fame, if kept.
+    pub fn evaluate(&mut self) -> Result<(), String> {
+        let mut batch = Batch::new(self.config.battle.clone());
+        batch.seed = Some(self.rng.gen());
+        if let Some(threads) = self.config.threads {
+            batch.threads = threads;
+        }
+
+        let mut scores = match &self.config.benchmark {
+            None => self.round_robin(&batch)?,
+            Some(benchmark) => {
+                let mut scores = benchmark.score(&self.population, &batch)?;
+                if self.config.pool_weight > 0. {
+                    for (score, pool) in scores.iter_mut().zip(self.round_robin(&batch)?) {
+                        *score += self.config.pool_weight * pool;
+                    }
+                }
+                scores
+            }
+        };
+
+        // past champions have to be beaten as well
+        if let Some(hall_of_fame) = &self.hall_of_fame {
+            let archive = hall_of_fame.score(&self.population, &batch, &mut self.rng)?;
+            for (score, archive) in scores.iter_mut().zip(archive) {
+                *score += archive;
+            }
+        }
+
+        self.scores = scores;
+        Ok(())
+    }
+
+    /// Points each warrior earns per round against every other one, times
+    /// 100 like a benchmark score.
+    fn round_robin(&self, batch: &Batch) -> Result<Vec<f64>, String> {
+        let n = self.population.len();
+        let matchups: Vec<Vec<usize>> = (0..n)
+            .flat_map(|a| (a + 1..n).map(move |b| vec![a, b]))
+            .collect();
+
+        let mut scores = vec![0.; n];
+        for (pair, result) in matchups.iter().zip(batch.run(&self.population, &matchups)?) {
+            scores[pair[0]] += result.scores[0];
+            scores[pair[1]] += result.scores[1];
+        }
+
+        let rounds = ((n.max(1) - 1) * batch.config.rounds).max(1) as f64;
+        Ok(scores.into_iter().map(|s| s * 100. / rounds).collect())
+    }
+
+    /// Population indices from best to worst score, earlier warriors first
+    /// on ties.
+    pub fn ranking(&self) -> Vec<usize> {
+        let mut ranking: Vec<usize> = (0..self.scores.len()).collect();
+        ranking.sort_by(|a, b| self.scores[*b].total_cmp(&self.scores[*a]));
+        ranking
+    }
+
+    /// Picks the index of a parent according to the selection strategy.
+    pub fn select(&mut self, ranking: &[usize]) -> usize {
+        match self.config.selection {
+            Selection::Truncation(fraction) => {
+                let cut =
+                    ((ranking.len() as f64 * fraction).ceil() as usize).clamp(1, ranking.len());
+                ranking[self.rng.gen_range(0..cut)]
+            }
+            Selection::Tournament(size) => (0..size.max(1))
+                .map(|_| self.rng.gen_range(0..self.scores.len()))
+                .max_by(|a, b| self.scores[*a].total_cmp(&self.scores[*b]))
+                .unwrap(),
+            Selection::Roulette => {
+                let total: f64 = self.scores.iter().sum();
+                if total <= 0. {
+                    return self.rng.gen_range(0..self.scores.len());
+                }
+
+                let mut pick = self.rng.gen_range(0. ..total);
+                for (i, score) in self.scores.iter().enumerate() {
+                    if pick < *score {
+                        return i;
+                    }
+                    pick -= score;
+                }
+                ranking[0]
+            }
+        }
+    }
+
+    /// Evaluates the current generation, unless it's been already, reports on
+    /// it and replaces it with its offspring.
+    pub fn step(&mut self) -> Result<GenerationReport, String> {
+        if self.population.len() < 2 {
+            return Err("a population needs at least two warriors".into());
+        }
+
+        if self.scores.len() != self.population.len() {
+            if let Some(path) = &self.config.lineage {
+                let born: Vec<String> = self
+                    .population
+                    .iter()
+                    .filter(|w| w.lineage.generation == self.generation)
+                    .map(|w| w.lineage.to_csv())
+                    .collect();
+                append(path, Lineage::CSV_HEADER, &born)?;
+            }
+
+            self.evaluate()?;
+
+            if let Some(dir) = &self.config.checkpoint {
+                self.save_checkpoint(dir)?;
+            }
+        }
+
+        let ranking = self.ranking();
+        let report = GenerationReport::new(self.generation, &self.population, &self.scores);
+
+        if let Some(path) = &self.config.statistics {
+            match path.extension().is_some_and(|ext| ext == "jsonl") {
+                true => append(path, "", &[report.to_json()])?,
+                false => append(path, &GenerationReport::csv_header(), &[report.to_csv()])?,
+            }
+        }
+
+        if let Some(hall_of_fame) = &mut self.hall_of_fame {
+            hall_of_fame.add(self.generation, report.best.clone());
+        }
+
+        let mut next: Vec<Warrior> = ranking
+            .iter()
+            .take(self.config.elitism)
+            .map(|i| self.population[*i].clone())
+            .collect();
+
+        while next.len() < self.population.len() {
+            let parent = self.select(&ranking);
+            let mut offspring = self.population[parent].clone();
+            let mut lineage = Lineage {
+                id: self.next_id,
+                generation: self.generation + 1,
+                parents: vec![offspring.lineage.id],
+                operators: vec![],
+            };
+            self.next_id += 1;
+
+            if self.rng.gen_bool(self.config.crossover_rate) {
+                let other = self.select(&ranking);
+                let other = &self.population[other];
+                if let Some((child, operator)) =
+                    self.config
+                        .crossover
+                        .crossover(&offspring, other, &mut self.rng)
+                {
+                    offspring = child;
+                    lineage.parents.push(other.lineage.id);
+                    lineage.operators.push(operator.to_string());
+                }
+            }
+
+            if self.rng.gen_bool(self.config.mutation_rate) {
+                if let Some((mutant, operator)) =
+                    self.config.mutation.mutate(&offspring, &mut self.rng)
+                {
+                    offspring = mutant;
+                    lineage.operators.push(operator.to_string());
+                }
+            }
+
+            offspring.lineage = lineage;
+            next.push(offspring);
+        }
+
+        self.population = next;
+        self.scores.clear();
+        self.generation += 1;
+
+        Ok(report)
+    }
+
+    /// Runs the remaining generations, returning a report for each.
+    pub fn run(&mut self) -> Result<Vec<GenerationReport>, String> {
+        let mut reports = vec![];
+        while self.generation < self.config.generations {
+            reports.push(self.step()?);
+        }
+        Ok(reports)
+    }
+}
+
+/// A 32 byte ChaCha seed written as 64 hex digits.
+fn parse_seed(hex: &str) -> Option<[u8; 32]> {
+    let mut seed = [0; 32];
+    if hex.len() != 64 {
+        return None;
+    }
+
+    for (i, byte) in seed.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(2 * i..2 * i + 2)?, 16).ok()?;
+    }
+
+    Some(seed)
+}
@@ -0,0 +1,171 @@
+use std::fmt;
+
+use rand::{seq::index, Rng};
+
+use crate::{batch::Batch, benchmark::Benchmark, warrior::Warrior};
+
+/// Which archived champions candidates are scored against.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Sampling {
+    All,
+    /// The `n` most recent champions.
+    Latest(usize),
+    /// `n` champions drawn at random, every generation anew.
+    Random(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HallOfFameConfig {
+    /// Champions kept, the oldest are dropped first.
+    pub size: usize,
+    pub sampling: Sampling,
+    /// How much the score against the sampled champions, in benchmark
+    /// units, adds to a candidate's fitness.
+    pub weight: f64,
+}
+
+impl HallOfFameConfig {
+    pub fn new() -> Self {
+        Self {
+            size: 50,
+            sampling: Sampling::Random(10),
+            weight: 1.,
+        }
+    }
+}
+
+impl Default for HallOfFameConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Champion {
+    pub generation: usize,
+    pub warrior: Warrior,
+}
+
+/// The best warrior of past generations, which new candidates have to beat
+/// too so the population can't just cycle between strategies that beat
+/// each other.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HallOfFame {
+    pub config: HallOfFameConfig,
+    /// Oldest first.
+    pub champions: Vec<Champion>,
+}
+
+impl HallOfFame {
+    pub fn new(config: HallOfFameConfig) -> Self {
+        Self {
+            config,
+            champions: vec![],
+        }
+    }
+
+    pub fn add(&mut self, generation: usize, warrior: Warrior) {
+        self.champions.push(Champion {
+            generation,
+            warrior,
+        });
+
+        let excess = self.champions.len().saturating_sub(self.config.size);
+        self.champions.drain(..excess);
+    }
+
+    /// Champions to score candidates against this generation, in archive
+    /// order.
+    pub fn sample(&self, rng: &mut impl Rng) -> Vec<Warrior> {
+        let len = self.champions.len();
+        let picked: Vec<usize> = match self.config.sampling {
+            Sampling::All => (0..len).collect(),
+            Sampling::Latest(n) => (len.saturating_sub(n)..len).collect(),
+            Sampling::Random(n) => {
+                let mut picked = index::sample(rng, len, n.min(len)).into_vec();
+                picked.sort();
+                picked
+            }
+        };
+
+        picked
+            .into_iter()
+            .map(|i| self.champions[i].warrior.clone())
+            .collect()
+    }
+
+    /// Fitness each of `warriors` earns against a sample of the archive,
+    /// nothing while it's empty.
+    pub fn score(
+        &self,
+        warriors: &[Warrior],
+        batch: &Batch,
+        rng: &mut impl Rng,
+    ) -> Result<Vec<f64>, String> {
+        let sample = self.sample(rng);
+        if sample.is_empty() || self.config.weight == 0. {
+            return Ok(vec![0.; warriors.len()]);
+        }
+
+        let mut opponents = Benchmark::new(sample);
+        opponents.rounds = batch.config.rounds;
+
+        let scores = opponents.score(warriors, batch)?;
+        Ok(scores.into_iter().map(|s| s * self.config.weight).collect())
+    }
+
+    /// Plays every champion against every older one, see `Ciao`.
+    pub fn ciao(&self, batch: &Batch) -> Result<Ciao, String> {
+        let warriors: Vec<Warrior> = self.champions.iter().map(|c| c.warrior.clone()).collect();
+        let matchups: Vec<Vec<usize>> = (0..warriors.len())
+            .flat_map(|i| (0..i).map(move |j| vec![i, j]))
+            .collect();
+
+        let mut scores: Vec<Vec<f64>> = (0..warriors.len()).map(|i| vec![0.; i]).collect();
+        let rounds = batch.config.rounds.max(1) as f64;
+        for (matchup, result) in matchups.iter().zip(batch.run(&warriors, &matchups)?) {
+            scores[matchup[0]][matchup[1]] = result.scores[0] * 100. / rounds;
+        }
+
+        Ok(Ciao {
+            generations: self.champions.iter().map(|c| c.generation).collect(),
+            scores,
+        })
+    }
+}
+
+/// Current Individual vs Ancestral Opponents: how the champion of each
+/// generation fares against the champions before it. Rows that get darker
+/// towards the left mean champions keep beating ever older ones, rows that
+/// alternate mean the population is going round in circles.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ciao {
+    pub generations: Vec<usize>,
+    /// `scores[i][j]`, for `j < i`, is the benchmark score of champion `i`
+    /// against champion `j`, 0 to 300.
+    pub scores: Vec<Vec<f64>>,
+}
+
+impl fmt::Display for Ciao {
+    /// One row per champion, one character per older champion, from ` `
+    /// for losing every round to `@` for winning every one.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const SHADES: &[u8] = b" .:-=+*#%@";
+
+        for (generation, row) in self.generations.iter().zip(&self.scores) {
+            write!(f, "{generation:>6} ")?;
+            for score in row {
+                let shade = (score / 300. * (SHADES.len() - 1) as f64).round() as usize;
+                write!(f, "{}", SHADES[shade.min(SHADES.len() - 1)] as char)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,511 @@
+use std::{
+    fmt,
+    fs::{self, OpenOptions},
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    batch::Batch,
+    battle::{BattleConfig, MatchResult},
+    warrior::Warrior,
+};
+
+/// Members kept by the usual King of the Hill hills.
+pub const HILL_SIZE: usize = 20;
+
+/// Largest submission read from a connection, in bytes.
+pub const MAX_SUBMISSION: u64 = 64 * 1024;
+
+/// How long a client may go quiet while sending or receiving.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct HillMember {
+    pub id: usize,
+    pub warrior: Warrior,
+    pub source: String,
+    /// Challenges survived since the member entered the hill.
+    pub age: usize,
+}
+
+/*
+    A hill lives in its own directory:
+
+        rules           the standard and the BattleConfig it is played under
+        members         the next submission id, then "<id> <age>" per member
+        scores          scores[i][j], points member i earned against member j
+        history.log     one line per challenge
+        warriors/<id>.red
+*/
+
+pub struct Hill {
+    pub dir: PathBuf,
+    pub standard: String,
+    pub config: BattleConfig,
+    pub size: usize,
+    pub members: Vec<HillMember>,
+    pub scores: Vec<Vec<f64>>,
+    pub threads: usize,
+    next_id: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChallengeReport {
+    pub name: String,
+    /// Place the challenger took on the hill, None if it didn't make it.
+    pub rank: Option<usize>,
+    pub pushed_off: Option<String>,
+    /// Name, score and age of every member after the challenge, best first.
+    pub standings: Vec<(String, f64, usize)>,
+}
+
+impl fmt::Display for ChallengeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.rank {
+            Some(rank) => writeln!(f, "{} entered the hill at rank {rank}", self.name)?,
+            None => writeln!(f, "{} did not make it onto the hill", self.name)?,
+        }
+
+        if let Some(pushed_off) = &self.pushed_off {
+            writeln!(f, "{pushed_off} was pushed off the hill")?;
+        }
+
+        writeln!(
+            f,
+            "{:>4}  {:<24} {:>10} {:>5}",
+            "Rank", "Name", "Score", "Age"
+        )?;
+        for (rank, (name, score, age)) in self.standings.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4}  {:<24} {:>10.2} {:>5}",
+                rank + 1,
+                name,
+                score,
+                age
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn io_err(path: &Path) -> impl Fn(std::io::Error) -> String + '_ {
+    move |err| format!("{}: {err}", path.display())
+}
+
+impl Hill {
+    /// Starts an empty hill in `dir`, which must not hold a hill already.
+    pub fn create(
+        dir: &Path,
+        standard: &str,
+        config: BattleConfig,
+        size: usize,
+    ) -> Result<Self, String> {
+        if dir.join("rules").exists() {
+            return Err(format!("{} already holds a hill", dir.display()));
+        }
+
+        fs::create_dir_all(dir.join("warriors")).map_err(io_err(dir))?;
+
+        let hill = Self {
+            dir: dir.to_path_buf(),
+            standard: standard.into(),
+            config,
+            size,
+            members: vec![],
+            scores: vec![],
+            threads: thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            next_id: 0,
+        };
+
+        hill.save()?;
+
+        Ok(hill)
+    }
+
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        let read = |name: &str| {
+            let path = dir.join(name);
+            fs::read_to_string(&path).map_err(io_err(&path))
+        };
+        let number = |s: &str| {
+            s.parse::<usize>()
+                .map_err(|_| format!("{}: \"{s}\" is not a number", dir.display()))
+        };
+
+        let mut standard = String::new();
+        let mut config = BattleConfig::new(8000);
+        let mut size = HILL_SIZE;
+
+        for line in read("rules")?.lines() {
+            let Some((key, value)) = line.split_once(' ') else {
+                continue;
+            };
+
+            match key {
+                "standard" => standard = value.into(),
+                "size" => size = number(value)?,
+                "core_size" => config.core_size = number(value)?,
+                "max_cycles" => config.max_cycles = number(value)?,
+                "max_processes" => config.max_processes = number(value)?,
+                "max_length" => config.max_length = number(value)?,
+                "min_distance" => config.min_distance = number(value)?,
+                "rounds" => config.rounds = number(value)?,
+                _ => return Err(format!("unknown hill rule {key}")),
+            }
+        }
+
+        let members_file = read("members")?;
+        let mut lines = members_file.lines();
+        let next_id = number(lines.next().unwrap_or("0"))?;
+
+        let mut members = vec![];
+        for line in lines {
+            let (id, age) = line
+                .split_once(' ')
+                .ok_or(format!("malformed hill member \"{line}\""))?;
+            let id = number(id)?;
+            let source = read(&format!("warriors/{id}.red"))?;
+            let name = Warrior::declared_name(&source).unwrap_or_else(|| format!("#{id}"));
+
+            members.push(HillMember {
+                id,
+                warrior: Warrior::parse(source.clone(), name, config.core_size)?,
+                source,
+                age: number(age)?,
+            });
+        }
+
+        let scores = read("scores")?
+            .lines()
+            .map(|line| {
+                line.split_whitespace()
+                    .map(|s| s.parse::<f64>().map_err(|_| format!("bad score \"{s}\"")))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if scores.len() != members.len() {
+            return Err(format!("{}: scores don't match members", dir.display()));
+        }
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            standard,
+            config,
+            size,
+            members,
+            scores,
+            threads: thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            next_id,
+        })
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let write = |name: &str, contents: String| {
+            let path = self.dir.join(name);
+            fs::write(&path, contents).map_err(io_err(&path))
+        };
+
+        let c = &self.config;
+        write(
+            "rules",
+            format!(
+                "standard {}\nsize {}\ncore_size {}\nmax_cycles {}\nmax_processes {}\nmax_length {}\nmin_distance {}\nrounds {}\n",
+                self.standard,
+                self.size,
+                c.core_size,
+                c.max_cycles,
+                c.max_processes,
+                c.max_length,
+                c.min_distance,
+                c.rounds
+            ),
+        )?;
+
+        let mut members = format!("{}\n", self.next_id);
+        for member in self.members.iter() {
+            members.push_str(&format!("{} {}\n", member.id, member.age));
+        }
+        write("members", members)?;
+
+        let scores: Vec<String> = self
+            .scores
+            .iter()
+            .map(|row| {
+                let row: Vec<String> = row.iter().map(|s| s.to_string()).collect();
+                row.join(" ") + "\n"
+            })
+            .collect();
+        write("scores", scores.concat())
+    }
+
+    fn log(&self, line: &str) -> Result<(), String> {
+        let path = self.dir.join("history.log");
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|t| t.as_secs())
+            .unwrap_or(0);
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(io_err(&path))?;
+
+        writeln!(file, "{time} [{}] {line}", self.standard).map_err(io_err(&path))
+    }
+
+    pub fn total_score(&self, member: usize) -> f64 {
+        self.scores[member].iter().sum()
+    }
+
+    /// Battles `source` against every member, then keeps the best `size`
+    /// warriors and persists the new hill.
+    pub fn challenge(&mut self, source: &str) -> Result<ChallengeReport, String> {
+        let id = self.next_id;
+        let name = Warrior::declared_name(source).unwrap_or_else(|| format!("#{id}"));
+        let challenger = Warrior::parse(source.into(), name.clone(), self.config.core_size)?;
+
+        if challenger.body.is_empty() {
+            return Err(format!("{name} has no instructions"));
+        }
+
+        let results = self.battle_members(&challenger)?;
+
+        self.next_id += 1;
+        fs::write(self.dir.join(format!("warriors/{id}.red")), source)
+            .map_err(io_err(&self.dir))?;
+
+        for (row, result) in self.scores.iter_mut().zip(results.iter()) {
+            row.push(result.scores[1]);
+        }
+        let mut row: Vec<f64> = results.iter().map(|r| r.scores[0]).collect();
+        row.push(0.);
+        self.scores.push(row);
+
+        for member in self.members.iter_mut() {
+            member.age += 1;
+        }
+        self.members.push(HillMember {
+            id,
+            warrior: challenger,
+            source: source.into(),
+            age: 0,
+        });
+
+        let mut pushed_off = None;
+        if self.members.len() > self.size {
+            let worst = (0..self.members.len())
+                .min_by(|a, b| self.total_score(*a).total_cmp(&self.total_score(*b)))
+                .unwrap();
+
+            self.scores.remove(worst);
+            for row in self.scores.iter_mut() {
+                row.remove(worst);
+            }
+            pushed_off = Some(self.members.remove(worst));
+        }
+
+        let mut ranking: Vec<usize> = (0..self.members.len()).collect();
+        ranking.sort_by(|a, b| self.total_score(*b).total_cmp(&self.total_score(*a)));
+
+        let report = ChallengeReport {
+            name: name.clone(),
+            rank: ranking
+                .iter()
+                .position(|m| self.members[*m].id == id)
+                .map(|rank| rank + 1),
+            pushed_off: pushed_off.as_ref().map(|m| m.warrior.name.clone()),
+            standings: ranking
+                .iter()
+                .map(|m| {
+                    let member = &self.members[*m];
+                    (
+                        member.warrior.name.clone(),
+                        self.total_score(*m),
+                        member.age,
+                    )
+                })
+                .collect(),
+        };
+
+        self.save()?;
+        self.log(&match (report.rank, &report.pushed_off) {
+            (Some(rank), Some(off)) => format!("{name} ({id}) placed {rank}, {off} pushed off"),
+            (Some(rank), None) => format!("{name} ({id}) placed {rank}"),
+            (None, _) => format!("{name} ({id}) failed to place"),
+        })?;
+
+        Ok(report)
+    }
+
+    fn battle_members(&self, challenger: &Warrior) -> Result<Vec<MatchResult>, String> {
+        // the challenger goes last so member indices stay as they are
+        let mut warriors: Vec<Warrior> = self.members.iter().map(|m| m.warrior.clone()).collect();
+        warriors.push(challenger.clone());
+
+        let matchups: Vec<Vec<usize>> = (0..self.members.len())
+            .map(|i| vec![self.members.len(), i])
+            .collect();
+
+        let mut batch = Batch::new(self.config.clone());
+        batch.threads = self.threads;
+        batch.run(&warriors, &matchups)
+    }
+
+    /// Challenges the hill with every `.red` file waiting in `spool`, moving
+    /// each one to `spool/done` alongside a report of how it went. A file
+    /// that can't be read or moved there is reported on stderr and moved to
+    /// `spool/failed` instead, so it holds up none of the others.
+    pub fn process_spool(&mut self, spool: &Path) -> Result<Vec<ChallengeReport>, String> {
+        let done = spool.join("done");
+        fs::create_dir_all(&done).map_err(io_err(&done))?;
+
+        let mut paths: Vec<PathBuf> = fs::read_dir(spool)
+            .map_err(io_err(spool))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "red"))
+            .collect();
+        paths.sort();
+
+        let mut reports = vec![];
+
+        for path in paths {
+            match self.process_spool_entry(&path, &done) {
+                Ok(report) => reports.extend(report),
+                Err(err) => {
+                    eprintln!("{err}");
+                    let failed = spool.join("failed");
+                    let file_name = path.file_name().unwrap_or_default();
+                    if let Err(err) = fs::create_dir_all(&failed)
+                        .and_then(|_| fs::rename(&path, failed.join(file_name)))
+                    {
+                        eprintln!("moving {} aside failed: {err}", path.display());
+                    }
+                }
+            }
+        }
+
+        Ok(reports)
+    }
+
+    /// Plays the submission at `path` and moves it to `done`, returning the
+    /// report unless it was rejected.
+    fn process_spool_entry(
+        &mut self,
+        path: &Path,
+        done: &Path,
+    ) -> Result<Option<ChallengeReport>, String> {
+        let source = fs::read_to_string(path).map_err(io_err(path))?;
+        let file_name = path.file_name().unwrap_or_default();
+
+        let (report, text) = match self.challenge(&source) {
+            Ok(report) => {
+                let text = report.to_string();
+                (Some(report), text)
+            }
+            Err(err) => (None, format!("rejected: {err}\n")),
+        };
+
+        let target = done.join(file_name);
+        fs::rename(path, &target).map_err(io_err(path))?;
+        fs::write(target.with_extension("txt"), text).map_err(io_err(&target))?;
+        Ok(report)
+    }
+
+    /// Processes the spool every `poll`, for good. Failures are reported on
+    /// stderr and the spool polled again.
+    pub fn serve_spool(&mut self, spool: &Path, poll: Duration) -> ! {
+        loop {
+            match self.process_spool(spool) {
+                Ok(reports) => {
+                    for report in reports {
+                        print!("{report}");
+                    }
+                }
+                Err(err) => eprintln!("{err}"),
+            }
+
+            thread::sleep(poll);
+        }
+    }
+
+    /// Reads a warrior until the client shuts down its side of the
+    /// connection and answers with the challenge report.
+    pub fn handle_connection(&mut self, stream: &mut TcpStream) -> Result<(), String> {
+        let source = read_submission(stream)?;
+        self.answer(stream, &source)
+    }
+
+    fn answer(&mut self, stream: &mut TcpStream, source: &str) -> Result<(), String> {
+        let answer = match self.challenge(source) {
+            Ok(report) => report.to_string(),
+            Err(err) => format!("rejected: {err}\n"),
+        };
+
+        stream
+            .set_write_timeout(Some(TIMEOUT))
+            .and_then(|_| stream.write_all(answer.as_bytes()))
+            .map_err(|err| format!("answering submission failed: {err}"))
+    }
+
+    /// Every connection is read on a thread of its own, so a slow client
+    /// holds up nobody else, and challenges are played one at a time in the
+    /// order their submissions came in.
+    pub fn serve_tcp(&mut self, addr: &str) -> Result<(), String> {
+        let listener = TcpListener::bind(addr).map_err(|err| format!("{addr}: {err}"))?;
+        let (sender, submissions) = mpsc::channel();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let sender = sender.clone();
+                        thread::spawn(move || match read_submission(&stream) {
+                            Ok(source) => {
+                                let _ = sender.send((stream, source));
+                            }
+                            Err(err) => eprintln!("{err}"),
+                        });
+                    }
+                    Err(err) => eprintln!("{err}"),
+                }
+            }
+        });
+
+        for (mut stream, source) in submissions {
+            if let Err(err) = self.answer(&mut stream, &source) {
+                eprintln!("{err}");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads a submission until the client shuts down its side of the
+/// connection, giving up on clients that go quiet or send too much.
+pub fn read_submission(stream: &TcpStream) -> Result<String, String> {
+    let mut source = String::new();
+    stream
+        .set_read_timeout(Some(TIMEOUT))
+        .and_then(|_| stream.take(MAX_SUBMISSION + 1).read_to_string(&mut source))
+        .map_err(|err| format!("reading submission failed: {err}"))?;
+
+    if source.len() as u64 > MAX_SUBMISSION {
+        return Err(format!("submission is over {MAX_SUBMISSION} bytes"));
+    }
+    Ok(source)
+}
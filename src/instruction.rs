@@ -1,3 +1,4 @@
+pub mod cell;
 pub mod decrement;
 pub mod field;
 pub mod instruction;
@@ -0,0 +1,308 @@
+use crate::{
+    core::{handlers::decode, CoreRuntime},
+    utils::ModUsize,
+};
+
+use super::{
+    decrement::Decrement, field::Field, instruction::Instruction, op_code::OpCode,
+    op_modifier::OpModifier,
+};
+
+/// Addressing mode of a field, with its increment folded in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum Mode {
+    Inmediate,
+    Direct,
+    AIndirect,
+    APredecrement,
+    APostincrement,
+    BIndirect,
+    BPredecrement,
+    BPostincrement,
+}
+
+/// Every `Mode` by its discriminant.
+const MODES: [Mode; 8] = [
+    Mode::Inmediate,
+    Mode::Direct,
+    Mode::AIndirect,
+    Mode::APredecrement,
+    Mode::APostincrement,
+    Mode::BIndirect,
+    Mode::BPredecrement,
+    Mode::BPostincrement,
+];
+
+/// An instruction as stored in the core: twelve bytes, with the core size
+/// kept once on `CoreRuntime` instead of on every value and both modes
+/// packed into a byte, leaving one for the predecoded handler. The opcode,
+/// modifier and modes are only changed through setters, which decode the
+/// handler again.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "Unpacked", into = "Unpacked")
+)]
+pub struct Cell {
+    code: OpCode,
+    modifier: OpModifier,
+    /// The A mode in the low three bits, the B mode in the next three.
+    modes: u8,
+    handler: u8,
+    values: [u32; 2],
+}
+
+/// A cell as it's saved, the handler is decoded again on loading rather
+/// than trusted.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Unpacked {
+    code: OpCode,
+    modifier: OpModifier,
+    modes: [Mode; 2],
+    values: [u32; 2],
+}
+
+#[cfg(feature = "serde")]
+impl From<Unpacked> for Cell {
+    fn from(cell: Unpacked) -> Self {
+        Cell::new(cell.code, cell.modifier, cell.modes, cell.values)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Cell> for Unpacked {
+    fn from(cell: Cell) -> Self {
+        Self {
+            code: cell.code,
+            modifier: cell.modifier,
+            modes: cell.modes(),
+            values: cell.values,
+        }
+    }
+}
+
+impl Mode {
+    fn from_field(field: &Field) -> Self {
+        match field {
+            Field::Inmediate(_) => Mode::Inmediate,
+            Field::Direct(_) => Mode::Direct,
+            Field::AIndirect(_, Decrement::None) => Mode::AIndirect,
+            Field::AIndirect(_, Decrement::Predecrement) => Mode::APredecrement,
+            Field::AIndirect(_, Decrement::Postincrement) => Mode::APostincrement,
+            Field::BIndirect(_, Decrement::None) => Mode::BIndirect,
+            Field::BIndirect(_, Decrement::Predecrement) => Mode::BPredecrement,
+            Field::BIndirect(_, Decrement::Postincrement) => Mode::BPostincrement,
+        }
+    }
+
+    fn to_field(self, val: ModUsize) -> Field {
+        match self {
+            Mode::Inmediate => Field::Inmediate(val),
+            Mode::Direct => Field::Direct(val),
+            Mode::AIndirect => Field::AIndirect(val, Decrement::None),
+            Mode::APredecrement => Field::AIndirect(val, Decrement::Predecrement),
+            Mode::APostincrement => Field::AIndirect(val, Decrement::Postincrement),
+            Mode::BIndirect => Field::BIndirect(val, Decrement::None),
+            Mode::BPredecrement => Field::BIndirect(val, Decrement::Predecrement),
+            Mode::BPostincrement => Field::BIndirect(val, Decrement::Postincrement),
+        }
+    }
+
+    /// The field of the pointed to cell an indirect mode goes through.
+    pub fn indirect_field(&self) -> Option<usize> {
+        match self {
+            Mode::Inmediate | Mode::Direct => None,
+            Mode::AIndirect | Mode::APredecrement | Mode::APostincrement => Some(0),
+            Mode::BIndirect | Mode::BPredecrement | Mode::BPostincrement => Some(1),
+        }
+    }
+
+    /// Resolves the address a field of this mode refers to, applying any
+    /// predecrement. Postincrements are left to `post_increment`, as ICWS '94
+    /// applies them only after the operand instruction has been copied.
+    pub fn solve(&self, value: u32, core: &mut CoreRuntime, ic: usize) -> usize {
+        let m = core.core_size;
+        let pointer = wrap(ic + value as usize, m);
+
+        match self.indirect_field() {
+            None if *self == Mode::Inmediate => ic,
+            None => pointer,
+            Some(i) => {
+                let cell = &mut core.core[pointer];
+                if matches!(self, Mode::APredecrement | Mode::BPredecrement) {
+                    cell.set_value(i, wrap(cell.value(i) as usize + m - 1, m) as u32);
+                }
+
+                wrap(pointer + cell.value(i) as usize, m)
+            }
+        }
+    }
+
+    pub fn post_increment(&self, value: u32, core: &mut CoreRuntime, ic: usize) {
+        let i = match self {
+            Mode::APostincrement => 0,
+            Mode::BPostincrement => 1,
+            _ => return,
+        };
+
+        let m = core.core_size;
+        let cell = &mut core.core[wrap(ic + value as usize, m)];
+        cell.set_value(i, wrap(cell.value(i) as usize + 1, m) as u32);
+    }
+}
+
+impl Cell {
+    /// The cell every address of a fresh core holds.
+    pub const EMPTY: Cell = Cell {
+        code: OpCode::DAT,
+        modifier: OpModifier::Default,
+        modes: Mode::Direct as u8 | (Mode::Direct as u8) << 3,
+        handler: decode(OpCode::DAT, OpModifier::F),
+        values: [0; 2],
+    };
+
+    pub fn new(code: OpCode, modifier: OpModifier, modes: [Mode; 2], values: [u32; 2]) -> Self {
+        let mut cell = Self {
+            code,
+            modifier,
+            modes: 0,
+            handler: 0,
+            values,
+        };
+        cell.set_modes(modes);
+        cell
+    }
+
+    pub fn code(&self) -> OpCode {
+        self.code
+    }
+
+    /// The modifier as written, `OpModifier::Default` included.
+    pub fn modifier(&self) -> OpModifier {
+        self.modifier
+    }
+
+    #[inline(always)]
+    pub fn mode(&self, i: usize) -> Mode {
+        MODES[(self.modes >> (3 * i) & 0b111) as usize]
+    }
+
+    #[inline(always)]
+    pub fn modes(&self) -> [Mode; 2] {
+        [self.mode(0), self.mode(1)]
+    }
+
+    #[inline(always)]
+    pub fn value(&self, i: usize) -> u32 {
+        self.values[i]
+    }
+
+    #[inline(always)]
+    pub fn values(&self) -> [u32; 2] {
+        self.values
+    }
+
+    /// Index of the function that executes the cell, see `core::handlers`.
+    #[inline(always)]
+    pub(crate) fn handler(&self) -> u8 {
+        self.handler
+    }
+
+    pub fn set_code(&mut self, code: OpCode) {
+        self.code = code;
+        self.decode();
+    }
+
+    pub fn set_modifier(&mut self, modifier: OpModifier) {
+        self.modifier = modifier;
+        self.decode();
+    }
+
+    /// Sets both modes, which may change what a default modifier resolves to.
+    pub fn set_modes(&mut self, modes: [Mode; 2]) {
+        self.modes = modes[0] as u8 | (modes[1] as u8) << 3;
+        self.decode();
+    }
+
+    /// Values don't take part in decoding, so this is all the runtime
+    /// writes while executing.
+    #[inline(always)]
+    pub fn set_value(&mut self, i: usize, value: u32) {
+        self.values[i] = value;
+    }
+
+    fn decode(&mut self) {
+        self.handler = decode(self.code, self.get_modifier());
+    }
+
+    /// Resolves `OpModifier::Default`, see `Instruction::get_modifier`.
+    pub fn get_modifier(&self) -> OpModifier {
+        if self.modifier != OpModifier::Default {
+            return self.modifier;
+        }
+
+        OpModifier::default_for(
+            self.code,
+            self.mode(0) == Mode::Inmediate,
+            self.mode(1) == Mode::Inmediate,
+        )
+    }
+
+    /// Equality as seen by SEQ.I and SNE.I: opcode, effective modifier,
+    /// modes and values must match.
+    pub fn same_instruction(&self, other: &Cell) -> bool {
+        self.code == other.code
+            && self.get_modifier() == other.get_modifier()
+            && self.modes == other.modes
+            && self.values == other.values
+    }
+
+    pub fn to_instruction(&self, core_size: usize) -> Instruction {
+        let field = |i: usize| {
+            self.mode(i).to_field(ModUsize {
+                val: self.values[i] as usize,
+                congruence: core_size,
+            })
+        };
+
+        Instruction {
+            ptr_range: core_size,
+            core_size,
+            code: self.code,
+            modifier: self.modifier,
+            fields: [field(0), field(1)],
+        }
+    }
+}
+
+impl Cell {
+    /// Packs `instruction` for a core of `core_size` cells. Its values are
+    /// reduced to that core, the instruction may have been parsed for
+    /// another one.
+    pub fn from_instruction(instruction: &Instruction, core_size: usize) -> Self {
+        let value = |i: usize| (instruction.fields[i].get_val().val % core_size) as u32;
+
+        Self::new(
+            instruction.code,
+            instruction.modifier,
+            [
+                Mode::from_field(&instruction.fields[0]),
+                Mode::from_field(&instruction.fields[1]),
+            ],
+            [value(0), value(1)],
+        )
+    }
+}
+
+/// Reduces `x`, known to be below twice the core size, without a division.
+pub(crate) fn wrap(x: usize, core_size: usize) -> usize {
+    if x >= core_size {
+        x - core_size
+    } else {
+        x
+    }
+}
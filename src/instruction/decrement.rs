@@ -1,17 +1,18 @@
-use rand::seq::SliceRandom;
+use rand::{seq::SliceRandom, Rng};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Decrement {
     None,
     Predecrement,
     Postincrement,
 }
 impl Decrement {
-    pub fn get_random() -> Decrement {
+    pub fn get_random(rng: &mut impl Rng) -> Decrement {
         use Decrement::*;
         [None, Predecrement, Postincrement]
-            .choose(&mut rand::thread_rng())
+            .choose(rng)
+            .copied()
             .unwrap()
-            .clone()
     }
 }
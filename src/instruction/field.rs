@@ -1,10 +1,13 @@
-use rand::seq::SliceRandom;
+use core::fmt;
 
-use crate::{core::CoreRuntime, utils::ModUsize};
+use rand::{seq::SliceRandom, Rng};
+
+use crate::utils::ModUsize;
 
 use super::decrement::Decrement;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Field {
     Direct(ModUsize),
     Inmediate(ModUsize),
@@ -13,23 +16,23 @@ pub enum Field {
 }
 
 impl Field {
-    pub fn get_random(ptr_range: usize, core_size: usize) -> Field {
+    pub fn get_random(ptr_range: usize, core_size: usize, rng: &mut impl Rng) -> Field {
         use Field::*;
         [
-            Direct(ModUsize::rand(core_size, 0..ptr_range)),
-            Inmediate(ModUsize::rand(core_size, 0..core_size)),
+            Direct(ModUsize::rand(core_size, 0..ptr_range, rng)),
+            Inmediate(ModUsize::rand(core_size, 0..core_size, rng)),
             AIndirect(
-                ModUsize::rand(core_size, 0..ptr_range),
-                Decrement::get_random(),
+                ModUsize::rand(core_size, 0..ptr_range, rng),
+                Decrement::get_random(rng),
             ),
             BIndirect(
-                ModUsize::rand(core_size, 0..ptr_range),
-                Decrement::get_random(),
+                ModUsize::rand(core_size, 0..ptr_range, rng),
+                Decrement::get_random(rng),
             ),
         ]
-        .choose(&mut rand::thread_rng())
+        .choose(rng)
+        .copied()
         .unwrap()
-        .clone()
     }
 
     pub fn get_val(&self) -> &ModUsize {
@@ -50,14 +53,6 @@ impl Field {
         } = data;
     }
 
-    fn decrement(&mut self) {
-        self.set_val(*self.get_val() - 1 as usize)
-    }
-
-    fn increment(&mut self) {
-        self.set_val(*self.get_val() + 1 as usize)
-    }
-
     fn num_parse(line: &str, core_size: usize) -> Result<ModUsize, String> {
         match str::parse::<isize>(line.into()) {
             Ok(i) => Ok(ModUsize::new(i, core_size)),
@@ -114,50 +109,26 @@ impl Field {
         Ok((Some(ret), splited.collect::<Vec<&str>>().join(" ")))
     }
 
-    pub fn solve(&self, core: &mut CoreRuntime, ic: ModUsize) -> ModUsize {
-        let ret = match (*self, 0, 1) {
-            (Field::Direct(p), _, _) => ic + p,
-            (Field::Inmediate(_), _, _) => ic,
-            (Field::AIndirect(x, m), i, _) | (Field::BIndirect(x, m), _, i) => {
-                let inst = core.get_mut_instruction_at(&(ic + x));
-                if let Decrement::Predecrement = m {
-                    inst.fields[i].decrement()
-                }
-
-                let ret = ic + x + *inst.fields[i].get_val();
-
-                if let Decrement::Postincrement = m {
-                    inst.fields[i].increment()
-                }
-
-                ret
-            }
-        };
-
-        // println!("[debug]: {self:?} was solved to {ret:?}");
-
-        ret
+    pub(crate) fn default(core_size: usize) -> Field {
+        Self::Direct(ModUsize::new(0, core_size))
     }
+}
 
-    #[cfg(not(tarpaulin_include))]
-    pub fn print(&self) {
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Field::Direct(x) => print!("{x}"),
-            Field::Inmediate(x) => print!("#{x}"),
+            Field::Direct(x) => write!(f, "{x}"),
+            Field::Inmediate(x) => write!(f, "#{x}"),
             Field::AIndirect(x, m) => match m {
-                Decrement::None => print!("*{x}"),
-                Decrement::Predecrement => print!("{{{x}"),
-                Decrement::Postincrement => print!("}}{x}"),
+                Decrement::None => write!(f, "*{x}"),
+                Decrement::Predecrement => write!(f, "{{{x}"),
+                Decrement::Postincrement => write!(f, "}}{x}"),
             },
             Field::BIndirect(x, m) => match m {
-                Decrement::None => print!("@{x}"),
-                Decrement::Predecrement => print!("<{x}"),
-                Decrement::Postincrement => print!(">{x}"),
+                Decrement::None => write!(f, "@{x}"),
+                Decrement::Predecrement => write!(f, "<{x}"),
+                Decrement::Postincrement => write!(f, ">{x}"),
             },
         }
     }
-
-    pub(crate) fn default(core_size: usize) -> Field {
-        Self::Direct(ModUsize::new(0, core_size))
-    }
 }
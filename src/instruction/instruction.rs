@@ -1,6 +1,11 @@
+use core::fmt;
+
+use rand::Rng;
+
 use super::{field::Field, op_code::OpCode, op_modifier::OpModifier};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Instruction {
     pub ptr_range: usize,
     pub core_size: usize,
@@ -11,60 +16,40 @@ pub struct Instruction {
 
 impl Instruction {
     pub fn get_random(ptr_range: usize, core_size: usize) -> Self {
+        Self::get_random_with_rng(ptr_range, core_size, &mut rand::thread_rng())
+    }
+
+    /// A random instruction whose non-immediate fields point within
+    /// `ptr_range` cells.
+    pub fn get_random_with_rng(ptr_range: usize, core_size: usize, rng: &mut impl Rng) -> Self {
         Self {
             core_size,
             ptr_range,
-            code: OpCode::get_random(),
-            modifier: OpModifier::get_random(),
+            code: OpCode::get_random(rng),
+            modifier: OpModifier::get_random(rng),
             fields: [
-                Field::get_random(ptr_range, core_size),
-                Field::get_random(ptr_range, core_size),
+                Field::get_random(ptr_range, core_size, rng),
+                Field::get_random(ptr_range, core_size, rng),
             ],
         }
     }
 
-    pub fn get_field_transmisions(&self) -> (Vec<(usize, usize)>, bool) {
-        match self.modifier {
-            OpModifier::A => (vec![(0, 0)], false),
-            OpModifier::B => (vec![(1, 1)], false),
-            OpModifier::AB => (vec![(0, 1)], false),
-            OpModifier::BA => (vec![(1, 0)], false),
-            OpModifier::F => (vec![(0, 0), (1, 1)], false),
-            OpModifier::X => (vec![(0, 1), (1, 0)], false),
-            OpModifier::I => (vec![(0, 0), (1, 1)], true),
-            OpModifier::Default => {
-                use OpCode::*;
-                match self.code {
-                    DAT | NOP => (vec![], false),
-                    MOV | SEQ | SNE | CMP => {
-                        if let Field::Inmediate(_) = self.fields[0] {
-                            (vec![(0, 1)], false)
-                        } else if let Field::Inmediate(_) = self.fields[1] {
-                            (vec![(1, 1)], false)
-                        } else {
-                            (vec![(0, 0), (1, 1)], true)
-                        }
-                    }
-                    ADD | SUB | MUL | DIV | MOD => {
-                        if let Field::Inmediate(_) = self.fields[0] {
-                            (vec![(0, 1)], false)
-                        } else if let Field::Inmediate(_) = self.fields[1] {
-                            (vec![(1, 1)], false)
-                        } else {
-                            (vec![(0, 0), (1, 1)], false)
-                        }
-                    }
-                    SLT /*| LDP | STP*/ => {
-                        if let Field::Inmediate(_) = self.fields[0] {
-                            (vec![(0, 1)], false)
-                        } else {
-                            (vec![(1, 1)], false)
-                        }
-                    }
-                    JMP | JMZ | JMN | DJN | SPL => (vec![(1, 1)], false),
-                }
-            }
+    /// Resolves `OpModifier::Default` into the modifier ICWS '94 assigns to
+    /// this opcode and addressing modes.
+    pub fn get_modifier(&self) -> OpModifier {
+        if self.modifier != OpModifier::Default {
+            return self.modifier;
         }
+
+        OpModifier::default_for(
+            self.code,
+            matches!(self.fields[0], Field::Inmediate(_)),
+            matches!(self.fields[1], Field::Inmediate(_)),
+        )
+    }
+
+    pub fn get_field_transmisions(&self) -> (&'static [(usize, usize)], bool) {
+        self.get_modifier().field_transmisions()
     }
 
     pub(crate) fn parse(line: String, core_size: usize) -> Result<Option<Self>, String> {
@@ -122,19 +107,20 @@ impl Instruction {
 
     #[cfg(not(tarpaulin_include))]
     pub(crate) fn print_state(&self) {
-        self.code.print();
-        self.modifier.print();
-        print!(" ");
-        self.fields[0].print();
-        print!(", ");
-        self.fields[1].print();
+        print!("{self}");
     }
 
-    pub(crate) fn get_field(&self, i: usize) -> Field {
-        self.fields[i]
+    pub(crate) fn mutate(&mut self, rng: &mut impl Rng) {
+        *self = Self::get_random_with_rng(self.ptr_range, self.core_size, rng)
     }
+}
 
-    pub(crate) fn mutate(&mut self) {
-        *self = Self::get_random(self.ptr_range, self.core_size)
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{} {}, {}",
+            self.code, self.modifier, self.fields[0], self.fields[1]
+        )
     }
 }
@@ -39,9 +39,12 @@ NOP
 
 */
 
-use rand::seq::SliceRandom;
+use core::fmt;
+
+use rand::{seq::SliceRandom, Rng};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OpCode {
     DAT, // — data
     MOV, // — move (copies data from address1 to address2)
@@ -64,15 +67,22 @@ pub enum OpCode {
     NOP, // — no operation (does nothing)
 }
 impl OpCode {
-    pub fn get_random() -> OpCode {
+    pub const ALL: [OpCode; 17] = {
+        use OpCode::*;
+        [
+            DAT, MOV, ADD, SUB, MUL, DIV, MOD, JMP, JMZ, JMN, DJN, SPL, CMP, SEQ, SNE, SLT, NOP,
+        ]
+    };
+
+    pub fn get_random(rng: &mut impl Rng) -> OpCode {
         use OpCode::*;
         [
             DAT, MOV, ADD, SUB, DAT, MOV, ADD, SUB, MUL, DIV, MOD, JMP, JMZ, JMN, DJN, SPL, CMP,
             SEQ, SNE, SLT, NOP, // LDP, STP,
         ]
-        .choose(&mut rand::thread_rng())
+        .choose(rng)
+        .copied()
         .unwrap()
-        .clone()
     }
 
     pub fn parse(line: String) -> Result<(Self, String), String> {
@@ -120,8 +130,10 @@ impl OpCode {
 
         Ok((code, line[3..].into()))
     }
+}
 
-    pub fn print(&self) {
-        print!("{self:?}");
+impl fmt::Display for OpCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
     }
 }
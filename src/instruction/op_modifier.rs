@@ -27,9 +27,14 @@ JMP, JMZ, JMN, DJN, SPL
 
 */
 
-use rand::seq::SliceRandom;
+use core::fmt;
+
+use rand::{seq::SliceRandom, Rng};
+
+use super::op_code::OpCode;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OpModifier {
     A,
     B,
@@ -41,12 +46,61 @@ pub enum OpModifier {
     Default,
 }
 impl OpModifier {
-    pub fn get_random() -> OpModifier {
+    /// The modifier ICWS '94 assigns to `code` when none is written, given
+    /// which of its fields are immediate.
+    pub fn default_for(code: OpCode, a_inmediate: bool, b_inmediate: bool) -> OpModifier {
+        use OpCode::*;
+        match code {
+            DAT | NOP => OpModifier::F,
+            MOV | SEQ | SNE | CMP => {
+                if a_inmediate {
+                    OpModifier::AB
+                } else if b_inmediate {
+                    OpModifier::B
+                } else {
+                    OpModifier::I
+                }
+            }
+            ADD | SUB | MUL | DIV | MOD => {
+                if a_inmediate {
+                    OpModifier::AB
+                } else if b_inmediate {
+                    OpModifier::B
+                } else {
+                    OpModifier::F
+                }
+            }
+            SLT /*| LDP | STP*/ => {
+                if a_inmediate {
+                    OpModifier::AB
+                } else {
+                    OpModifier::B
+                }
+            }
+            JMP | JMZ | JMN | DJN | SPL => OpModifier::B,
+        }
+    }
+
+    /// The (source, destination) field pairs this modifier works on, and
+    /// whether it applies to the whole instruction.
+    pub fn field_transmisions(&self) -> (&'static [(usize, usize)], bool) {
+        match self {
+            OpModifier::A => (&[(0, 0)], false),
+            OpModifier::B => (&[(1, 1)], false),
+            OpModifier::AB => (&[(0, 1)], false),
+            OpModifier::BA => (&[(1, 0)], false),
+            OpModifier::F | OpModifier::Default => (&[(0, 0), (1, 1)], false),
+            OpModifier::X => (&[(0, 1), (1, 0)], false),
+            OpModifier::I => (&[(0, 0), (1, 1)], true),
+        }
+    }
+
+    pub fn get_random(rng: &mut impl Rng) -> OpModifier {
         use OpModifier::*;
         [A, B, AB, BA, F, X, I]
-            .choose(&mut rand::thread_rng())
+            .choose(rng)
+            .copied()
             .unwrap()
-            .clone()
     }
 
     pub fn parse(line: String) -> Result<(Self, String), String> {
@@ -70,10 +124,12 @@ impl OpModifier {
             Ok((Self::Default, line))
         }
     }
+}
 
-    #[cfg(not(tarpaulin_include))]
-    pub fn print(&self) {
-        print!(
+impl fmt::Display for OpModifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
             "{}",
             match self {
                 OpModifier::A => ".A",
@@ -85,6 +141,6 @@ impl OpModifier {
                 OpModifier::I => ".I",
                 OpModifier::Default => "",
             },
-        );
+        )
     }
 }
@@ -0,0 +1,211 @@
+use std::{
+    path::{Path, PathBuf},
+    thread,
+};
+
+use crate::{
+    evolution::{Evolution, EvolutionConfig, GenerationReport},
+    warrior::Warrior,
+};
+
+/// Lineage ids of island `i` start at `i << ISLAND_ID_BITS`, so they stay
+/// unique across islands and migrants can be told by where they came from.
+pub const ISLAND_ID_BITS: u32 = 48;
+
+/// Which islands send their best warrior to which.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Topology {
+    /// Every island to the next one, the last to the first.
+    Ring,
+    /// Every island to every other one.
+    FullyConnected,
+}
+
+impl Topology {
+    /// Islands `island` receives migrants from, out of `islands`.
+    pub fn sources(&self, island: usize, islands: usize) -> Vec<usize> {
+        match self {
+            Topology::Ring if islands > 1 => vec![(island + islands - 1) % islands],
+            Topology::Ring => vec![],
+            Topology::FullyConnected => (0..islands).filter(|i| *i != island).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IslandsConfig {
+    /// One population per config, each evolving under its own parameters.
+    pub islands: Vec<EvolutionConfig>,
+    pub topology: Topology,
+    /// Generations between migrations.
+    pub interval: usize,
+    pub generations: usize,
+}
+
+impl IslandsConfig {
+    /// `islands` copies of `config`, seeded one after another if it is.
+    /// Island `i` checkpoints to `island_<i>` inside the checkpoint
+    /// directory, and logs to the statistics and lineage files with
+    /// `_island_<i>` added to their names.
+    pub fn new(config: EvolutionConfig, islands: usize) -> Self {
+        let generations = config.generations;
+        Self {
+            islands: (0..islands)
+                .map(|i| EvolutionConfig {
+                    seed: config.seed.map(|seed| seed.wrapping_add(i as u64)),
+                    checkpoint: config
+                        .checkpoint
+                        .as_ref()
+                        .map(|dir| dir.join(format!("island_{i}"))),
+                    statistics: config
+                        .statistics
+                        .as_deref()
+                        .map(|path| island_file(path, i)),
+                    lineage: config.lineage.as_deref().map(|path| island_file(path, i)),
+                    ..config.clone()
+                })
+                .collect(),
+            topology: Topology::Ring,
+            interval: 10,
+            generations,
+        }
+    }
+}
+
+/// `path` with `_island_<i>` after its file stem.
+fn island_file(path: &Path, i: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{stem}_island_{i}.{}", extension.to_string_lossy()),
+        None => format!("{stem}_island_{i}"),
+    };
+    path.with_file_name(name)
+}
+
+/// Independent populations evolving side by side, one thread each, that
+/// swap their best warriors every `interval` generations.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Islands {
+    pub config: IslandsConfig,
+    pub islands: Vec<Evolution>,
+}
+
+impl Islands {
+    pub fn new(config: IslandsConfig) -> Result<Self, String> {
+        let mut islands = config
+            .islands
+            .iter()
+            .cloned()
+            .map(Evolution::new)
+            .collect::<Result<Vec<_>, _>>()?;
+        for (i, island) in islands.iter_mut().enumerate() {
+            island.number_from((i as u64) << ISLAND_ID_BITS);
+        }
+        Ok(Self::with_islands(config, islands))
+    }
+
+    /// Starts from populations already under way, such as resumed ones.
+    /// Islands that don't say how many threads to use share the CPUs out
+    /// evenly, as they all evolve at once.
+    pub fn with_islands(config: IslandsConfig, mut islands: Vec<Evolution>) -> Self {
+        let cpus = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+        let threads = (cpus / islands.len().max(1)).max(1);
+        for island in islands.iter_mut() {
+            island.config.threads.get_or_insert(threads);
+        }
+
+        Self { config, islands }
+    }
+
+    /// Generation every island is at.
+    pub fn generation(&self) -> usize {
+        self.islands.iter().map(|i| i.generation).min().unwrap_or(0)
+    }
+
+    /// Evolves every island up to the next migration, or the last
+    /// generation, then migrates. Returns the reports of each island.
+    pub fn epoch(&mut self) -> Result<Vec<Vec<GenerationReport>>, String> {
+        let generation = self.generation();
+        if self.islands.iter().any(|i| i.generation != generation) {
+            return Err("islands are at different generations".into());
+        }
+
+        let interval = self.config.interval.max(1);
+        let until = (generation + interval - generation % interval).min(self.config.generations);
+
+        let reports = thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .islands
+                .iter_mut()
+                .map(|island| {
+                    scope.spawn(move || {
+                        let mut reports = vec![];
+                        while island.generation < until {
+                            reports.push(island.step()?);
+                        }
+                        Ok(reports)
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("an island panicked"))
+                .collect::<Result<Vec<Vec<GenerationReport>>, String>>()
+        })?;
+
+        let best: Vec<Option<Warrior>> = reports
+            .iter()
+            .map(|reports| reports.last().map(|report| report.best.clone()))
+            .collect();
+        self.migrate(&best);
+
+        Ok(reports)
+    }
+
+    /// Sends the best warrior of every island, where there is one, along the
+    /// topology. Migrants take the place of the last offspring, elites are
+    /// never replaced, and are born again with a "migration" lineage whose
+    /// parent is the warrior they copy.
+    pub fn migrate(&mut self, best: &[Option<Warrior>]) {
+        let n = self.islands.len();
+
+        for (i, island) in self.islands.iter_mut().enumerate() {
+            let migrants: Vec<&Warrior> = self
+                .config
+                .topology
+                .sources(i, n)
+                .into_iter()
+                .filter_map(|source| best.get(source)?.as_ref())
+                .collect();
+
+            let len = island.population.len();
+            let first = island.config.elitism.min(len);
+            let mut replaced = false;
+            for (slot, migrant) in (first..len).rev().zip(migrants) {
+                island.adopt(slot, migrant);
+                replaced = true;
+            }
+
+            // the scores, if any, were earned by the warriors replaced
+            if replaced {
+                island.scores.clear();
+            }
+        }
+    }
+
+    /// Runs the remaining generations, returning the reports of each island.
+    pub fn run(&mut self) -> Result<Vec<Vec<GenerationReport>>, String> {
+        let mut reports = vec![vec![]; self.islands.len()];
+        while self.generation() < self.config.generations {
+            for (all, epoch) in reports.iter_mut().zip(self.epoch()?) {
+                all.extend(epoch);
+            }
+        }
+        Ok(reports)
+    }
+}
@@ -0,0 +1,23 @@
+pub mod assembler;
+pub mod batch;
+pub mod battle;
+pub mod benchmark;
+pub mod cli;
+pub mod core;
+pub mod crossover;
+pub mod environment;
+pub mod evolution;
+pub mod hall_of_fame;
+pub mod hill;
+pub mod instruction;
+pub mod islands;
+pub mod map_elites;
+pub mod minimizer;
+pub mod mutation;
+pub mod optima;
+pub mod optimizer;
+pub mod settings;
+mod test;
+pub mod tournament;
+pub mod utils;
+pub mod warrior;
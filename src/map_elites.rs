@@ -0,0 +1,336 @@
+use std::collections::BTreeMap;
+
+use rand::{seq::index, Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+
+use crate::{
+    batch::Batch, battle::BattleConfig, core::stats::WarriorStats, evolution::EvolutionConfig,
+    warrior::Warrior,
+};
+
+/// A behavior measured while a warrior battles, a MAP-Elites grid axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Descriptor {
+    /// Processes started by SPL per round.
+    Processes,
+    /// Fraction of the core written per round.
+    CoreWritten,
+    /// Instructions in the warrior's body.
+    Length,
+    /// Cells written per instruction executed.
+    BombDensity,
+    /// Fraction of the instructions executed outside the loaded body, so
+    /// by code the warrior copied somewhere else.
+    Movement,
+}
+
+impl Descriptor {
+    pub const ALL: [Descriptor; 5] = [
+        Descriptor::Processes,
+        Descriptor::CoreWritten,
+        Descriptor::Length,
+        Descriptor::BombDensity,
+        Descriptor::Movement,
+    ];
+
+    pub fn measure(&self, warrior: &Warrior, stats: &WarriorStats, core_size: usize) -> f64 {
+        let rounds = stats.rounds.max(1) as f64;
+        let cycles = stats.cycles.max(1) as f64;
+
+        match self {
+            Descriptor::Processes => stats.spawned as f64 / rounds,
+            Descriptor::CoreWritten => stats.cells_written as f64 / (rounds * core_size as f64),
+            Descriptor::Length => warrior.body.len() as f64,
+            Descriptor::BombDensity => stats.writes as f64 / cycles,
+            Descriptor::Movement => stats.away as f64 / cycles,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Axis {
+    pub descriptor: Descriptor,
+    pub min: f64,
+    pub max: f64,
+    /// Values outside `min..max` fall in the first or last bin.
+    pub bins: usize,
+}
+
+impl Axis {
+    /// An axis over the values `descriptor` usually takes under `battle`.
+    pub fn new(descriptor: Descriptor, battle: &BattleConfig) -> Self {
+        let (min, max, bins) = match descriptor {
+            Descriptor::Processes => (0., battle.max_processes.min(64) as f64, 8),
+            Descriptor::CoreWritten => (0., 1., 8),
+            Descriptor::Length => (1., battle.max_length as f64, battle.max_length.min(10)),
+            // a MOV with two increments writes three cells
+            Descriptor::BombDensity => (0., 3., 8),
+            Descriptor::Movement => (0., 1., 8),
+        };
+
+        Self {
+            descriptor,
+            min,
+            max,
+            bins,
+        }
+    }
+
+    pub fn bin(&self, value: f64) -> usize {
+        let bins = self.bins.max(1);
+        if self.max <= self.min {
+            return 0;
+        }
+
+        let bin = ((value - self.min) / (self.max - self.min) * bins as f64).floor();
+        (bin.max(0.) as usize).min(bins - 1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MapElitesConfig {
+    pub axes: Vec<Axis>,
+    /// Variation, battle rules, benchmark and seed. `population` is the
+    /// number of candidates tried per iteration, `warrior_length` that of
+    /// the random ones the grid starts from.
+    pub evolution: EvolutionConfig,
+    /// Without a benchmark, elites each candidate plays against.
+    pub opponents: usize,
+    pub iterations: usize,
+}
+
+impl MapElitesConfig {
+    /// A grid over process count and fraction of the core written.
+    pub fn new(evolution: EvolutionConfig) -> Self {
+        Self {
+            axes: vec![
+                Axis::new(Descriptor::Processes, &evolution.battle),
+                Axis::new(Descriptor::CoreWritten, &evolution.battle),
+            ],
+            iterations: evolution.generations,
+            evolution,
+            opponents: 5,
+        }
+    }
+}
+
+/// The best warrior found so far for one niche.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Elite {
+    pub warrior: Warrior,
+    /// Benchmark score against the opponents it was evaluated against.
+    pub fitness: f64,
+    /// Value of every axis' descriptor, in axis order.
+    pub descriptors: Vec<f64>,
+}
+
+/// What happened in one iteration.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IterationReport {
+    pub iteration: usize,
+    /// Candidates that took over a cell.
+    pub inserted: usize,
+    /// Cells holding an elite.
+    pub filled: usize,
+    pub best_fitness: f64,
+}
+
+/// Quality-diversity search: rather than one population, a grid of cells
+/// indexed by behavior, each keeping the fittest warrior that behaves that
+/// way. Parents are drawn from the grid, offspring take over the cell they
+/// land in if they're fitter than its elite.
+pub struct MapElites {
+    pub config: MapElitesConfig,
+    /// Elites by their bin on every axis.
+    pub archive: BTreeMap<Vec<usize>, Elite>,
+    pub iteration: usize,
+    rng: ChaCha12Rng,
+}
+
+impl MapElites {
+    pub fn new(config: MapElitesConfig) -> Self {
+        let rng = match config.evolution.seed {
+            Some(seed) => ChaCha12Rng::seed_from_u64(seed),
+            None => ChaCha12Rng::from_entropy(),
+        };
+
+        Self {
+            config,
+            archive: BTreeMap::new(),
+            iteration: 0,
+            rng,
+        }
+    }
+
+    pub fn best(&self) -> Option<&Elite> {
+        self.archive
+            .values()
+            .max_by(|a, b| a.fitness.total_cmp(&b.fitness))
+    }
+
+    /// Random warriors while the grid is empty, offspring of random elites
+    /// afterwards.
+    fn candidates(&mut self) -> Vec<Warrior> {
+        let config = &self.config.evolution;
+        let elites: Vec<&Warrior> = self.archive.values().map(|e| &e.warrior).collect();
+
+        (0..config.population)
+            .map(|_| {
+                if elites.is_empty() {
+                    let core_size = config.battle.core_size;
+                    return Warrior::random_create_with_rng(
+                        config.warrior_length,
+                        core_size,
+                        &mut self.rng,
+                    );
+                }
+
+                let mut offspring = elites[self.rng.gen_range(0..elites.len())].clone();
+                if self.rng.gen_bool(config.crossover_rate) {
+                    let other = elites[self.rng.gen_range(0..elites.len())];
+                    if let Some((child, _)) =
+                        config.crossover.crossover(&offspring, other, &mut self.rng)
+                    {
+                        offspring = child;
+                    }
+                }
+
+                if self.rng.gen_bool(config.mutation_rate) {
+                    if let Some((mutant, _)) = config.mutation.mutate(&offspring, &mut self.rng) {
+                        offspring = mutant;
+                    }
+                }
+
+                offspring
+            })
+            .collect()
+    }
+
+    /// Plays every candidate against the benchmark or a sample of elites,
+    /// or the other candidates while there are none, and returns the
+    /// benchmark score and statistics of each.
+    pub fn evaluate(&mut self, candidates: &[Warrior]) -> Result<Vec<(f64, WarriorStats)>, String> {
+        let config = &self.config.evolution;
+        let n = candidates.len();
+
+        let opponents: Vec<Warrior> = match &config.benchmark {
+            Some(benchmark) => benchmark.warriors.clone(),
+            None => {
+                let elites: Vec<&Elite> = self.archive.values().collect();
+                let count = self.config.opponents.min(elites.len());
+                index::sample(&mut self.rng, elites.len(), count)
+                    .into_iter()
+                    .map(|i| elites[i].warrior.clone())
+                    .collect()
+            }
+        };
+
+        let mut pool = candidates.to_vec();
+        pool.extend(opponents.iter().cloned());
+
+        let matchups: Vec<Vec<usize>> = if opponents.is_empty() {
+            (0..n)
+                .flat_map(|a| (0..n).filter(move |b| *b != a).map(move |b| vec![a, b]))
+                .collect()
+        } else {
+            (0..n)
+                .flat_map(|c| (0..opponents.len()).map(move |o| vec![c, n + o]))
+                .collect()
+        };
+
+        let mut battle = config.battle.clone();
+        if let Some(benchmark) = &config.benchmark {
+            battle.rounds = benchmark.rounds;
+        }
+
+        let mut batch = Batch::new(battle);
+        batch.seed = Some(self.rng.gen());
+        batch.stats = true;
+
+        let mut evaluated = vec![(0., WarriorStats::default()); n];
+        let mut matches = vec![0; n];
+        for (matchup, result) in matchups.iter().zip(batch.run(&pool, &matchups)?) {
+            let (score, stats) = &mut evaluated[matchup[0]];
+            *score += result.scores[0];
+            stats.add(&result.stats[0]);
+            matches[matchup[0]] += 1;
+        }
+
+        let rounds = batch.config.rounds.max(1);
+        for ((score, _), matches) in evaluated.iter_mut().zip(matches) {
+            *score *= 100. / (matches * rounds).max(1) as f64;
+        }
+
+        Ok(evaluated)
+    }
+
+    /// Tries one batch of candidates, each taking over its cell if it's
+    /// empty or held by a less fit elite.
+    pub fn step(&mut self) -> Result<IterationReport, String> {
+        if self.config.evolution.population == 0 {
+            return Err("an iteration needs at least one candidate".into());
+        }
+        self.config.evolution.validate()?;
+
+        let candidates = self.candidates();
+        let evaluated = self.evaluate(&candidates)?;
+        let core_size = self.config.evolution.battle.core_size;
+
+        let mut inserted = 0;
+        for (warrior, (fitness, stats)) in candidates.into_iter().zip(evaluated) {
+            let descriptors: Vec<f64> = self
+                .config
+                .axes
+                .iter()
+                .map(|axis| axis.descriptor.measure(&warrior, &stats, core_size))
+                .collect();
+            let cell: Vec<usize> = self
+                .config
+                .axes
+                .iter()
+                .zip(&descriptors)
+                .map(|(axis, value)| axis.bin(*value))
+                .collect();
+
+            if self
+                .archive
+                .get(&cell)
+                .is_none_or(|elite| fitness > elite.fitness)
+            {
+                self.archive.insert(
+                    cell,
+                    Elite {
+                        warrior,
+                        fitness,
+                        descriptors,
+                    },
+                );
+                inserted += 1;
+            }
+        }
+
+        let report = IterationReport {
+            iteration: self.iteration,
+            inserted,
+            filled: self.archive.len(),
+            best_fitness: self.best().map_or(0., |elite| elite.fitness),
+        };
+        self.iteration += 1;
+
+        Ok(report)
+    }
+
+    /// Runs the remaining iterations, returning a report for each.
+    pub fn run(&mut self) -> Result<Vec<IterationReport>, String> {
+        let mut reports = vec![];
+        while self.iteration < self.config.iterations {
+            reports.push(self.step()?);
+        }
+        Ok(reports)
+    }
+}
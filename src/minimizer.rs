@@ -0,0 +1,149 @@
+use crate::{
+    batch::Batch,
+    benchmark::Benchmark,
+    instruction::{field::Field, instruction::Instruction},
+    utils::ModUsize,
+    warrior::Warrior,
+};
+
+/// Which edits the minimizer tries and how much score they may cost.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MinimizerConfig {
+    /// Benchmark points below the original score an edit may leave the
+    /// warrior at and still be kept.
+    pub tolerance: f64,
+    pub remove: bool,
+    /// Replace instructions with `DAT 0, 0`.
+    pub replace_with_dat: bool,
+    /// Zero field values and turn addressing modes direct.
+    pub simplify_fields: bool,
+}
+
+impl MinimizerConfig {
+    pub fn new() -> Self {
+        Self {
+            tolerance: 5.,
+            remove: true,
+            replace_with_dat: true,
+            simplify_fields: true,
+        }
+    }
+}
+
+impl Default for MinimizerConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Minimized {
+    pub warrior: Warrior,
+    pub score: f64,
+    pub original_score: f64,
+    /// Times the benchmark was played, the original included.
+    pub evaluations: usize,
+}
+
+/// Strips a warrior of the code it doesn't need to keep its benchmark score.
+/// Give the batch a seed, or the same warrior scores differently every time
+/// and noise decides which edits are kept.
+pub struct Minimizer {
+    pub config: MinimizerConfig,
+    pub benchmark: Benchmark,
+    pub batch: Batch,
+}
+
+impl Minimizer {
+    pub fn new(benchmark: Benchmark, batch: Batch) -> Self {
+        Self {
+            config: MinimizerConfig::new(),
+            benchmark,
+            batch,
+        }
+    }
+
+    /// Tries every edit on every instruction, last to first, keeping those
+    /// that don't cost more than the tolerance, until a whole pass keeps
+    /// none.
+    pub fn minimize(&self, warrior: &Warrior) -> Result<Minimized, String> {
+        let original_score = self.score(warrior)?;
+        let threshold = original_score - self.config.tolerance;
+
+        let mut minimized = Minimized {
+            warrior: warrior.clone(),
+            score: original_score,
+            original_score,
+            evaluations: 1,
+        };
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for i in (0..minimized.warrior.body.len()).rev() {
+                for candidate in self.edits(&minimized.warrior, i)? {
+                    let score = self.score(&candidate)?;
+                    minimized.evaluations += 1;
+                    if score >= threshold {
+                        minimized.warrior = candidate;
+                        minimized.score = score;
+                        changed = true;
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(minimized)
+    }
+
+    fn score(&self, warrior: &Warrior) -> Result<f64, String> {
+        Ok(self
+            .benchmark
+            .score(std::slice::from_ref(warrior), &self.batch)?[0])
+    }
+
+    /// The enabled edits of instruction `i` that change `warrior`, simplest
+    /// first.
+    fn edits(&self, warrior: &Warrior, i: usize) -> Result<Vec<Warrior>, String> {
+        let mut edits = vec![];
+        let len = warrior.body.len();
+
+        if self.config.remove && len > 1 {
+            let mut edit = warrior.clone();
+            edit.body.remove(i);
+            if i < edit.org {
+                edit.org -= 1;
+            }
+            edit.org = edit.org.min(len - 2);
+            edits.push(edit);
+        }
+
+        let core_size = warrior.body[i].core_size;
+        let dat = Instruction::parse("DAT 0, 0".into(), core_size)?.unwrap();
+        if self.config.replace_with_dat && warrior.body[i] != dat {
+            let mut edit = warrior.clone();
+            edit.body[i] = dat;
+            edits.push(edit);
+        }
+
+        if self.config.simplify_fields {
+            for f in 0..2 {
+                let field = warrior.body[i].fields[f];
+                let mut zeroed = field;
+                zeroed.set_val(ModUsize::new(0, core_size));
+                for simpler in [zeroed, Field::Direct(*field.get_val())] {
+                    if simpler != field {
+                        let mut edit = warrior.clone();
+                        edit.body[i].fields[f] = simpler;
+                        edits.push(edit);
+                    }
+                }
+            }
+        }
+
+        Ok(edits)
+    }
+}
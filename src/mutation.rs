@@ -0,0 +1,297 @@
+use core::fmt;
+
+use rand::Rng;
+
+use crate::{
+    battle::BattleConfig,
+    instruction::{
+        decrement::Decrement, field::Field, instruction::Instruction, op_code::OpCode,
+        op_modifier::OpModifier,
+    },
+    utils::ModUsize,
+    warrior::Warrior,
+};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MutationOperator {
+    /// Adds a small delta to one field value.
+    TweakValue,
+    /// Changes the addressing mode of one field, keeping its value.
+    ChangeMode,
+    ChangeOpcode,
+    ChangeModifier,
+    /// Inserts a random instruction.
+    Insert,
+    Delete,
+    /// Repeats an instruction right after itself.
+    Duplicate,
+    /// Swaps two instructions.
+    Swap,
+    /// Moves the starting instruction.
+    ShiftOrg,
+    /// Replaces a whole instruction with a random one.
+    Replace,
+    /// Sets one field value to one of `MutationConfig::constants`.
+    UseConstant,
+}
+
+impl MutationOperator {
+    pub const ALL: [MutationOperator; 11] = [
+        MutationOperator::TweakValue,
+        MutationOperator::ChangeMode,
+        MutationOperator::ChangeOpcode,
+        MutationOperator::ChangeModifier,
+        MutationOperator::Insert,
+        MutationOperator::Delete,
+        MutationOperator::Duplicate,
+        MutationOperator::Swap,
+        MutationOperator::ShiftOrg,
+        MutationOperator::Replace,
+        MutationOperator::UseConstant,
+    ];
+}
+
+impl fmt::Display for MutationOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                MutationOperator::TweakValue => "tweak_value",
+                MutationOperator::ChangeMode => "change_mode",
+                MutationOperator::ChangeOpcode => "change_opcode",
+                MutationOperator::ChangeModifier => "change_modifier",
+                MutationOperator::Insert => "insert",
+                MutationOperator::Delete => "delete",
+                MutationOperator::Duplicate => "duplicate",
+                MutationOperator::Swap => "swap",
+                MutationOperator::ShiftOrg => "shift_org",
+                MutationOperator::Replace => "replace",
+                MutationOperator::UseConstant => "use_constant",
+            }
+        )
+    }
+}
+
+/// Relative weights of the mutation operators, an operator weighted 0 is
+/// never applied.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MutationConfig {
+    pub tweak_value: f64,
+    pub change_mode: f64,
+    pub change_opcode: f64,
+    pub change_modifier: f64,
+    pub insert: f64,
+    pub delete: f64,
+    pub duplicate: f64,
+    pub swap: f64,
+    pub shift_org: f64,
+    pub replace: f64,
+    pub use_constant: f64,
+    /// Values `UseConstant` picks from, such as the steps `optima::optima`
+    /// finds for the core.
+    pub constants: Vec<usize>,
+    /// Largest change `TweakValue` makes, either way.
+    pub max_delta: usize,
+    /// Warriors aren't grown past this many instructions.
+    pub max_length: usize,
+    pub core_size: usize,
+}
+
+impl MutationConfig {
+    pub fn new(battle: &BattleConfig) -> Self {
+        Self {
+            tweak_value: 4.,
+            change_mode: 2.,
+            change_opcode: 2.,
+            change_modifier: 2.,
+            insert: 1.,
+            delete: 1.,
+            duplicate: 1.,
+            swap: 1.,
+            shift_org: 1.,
+            replace: 1.,
+            use_constant: 0.,
+            constants: vec![],
+            max_delta: 8,
+            max_length: battle.max_length,
+            core_size: battle.core_size,
+        }
+    }
+
+    pub fn weight(&self, operator: MutationOperator) -> f64 {
+        match operator {
+            MutationOperator::TweakValue => self.tweak_value,
+            MutationOperator::ChangeMode => self.change_mode,
+            MutationOperator::ChangeOpcode => self.change_opcode,
+            MutationOperator::ChangeModifier => self.change_modifier,
+            MutationOperator::Insert => self.insert,
+            MutationOperator::Delete => self.delete,
+            MutationOperator::Duplicate => self.duplicate,
+            MutationOperator::Swap => self.swap,
+            MutationOperator::ShiftOrg => self.shift_org,
+            MutationOperator::Replace => self.replace,
+            MutationOperator::UseConstant => self.use_constant,
+        }
+    }
+
+    pub fn set_weight(&mut self, operator: MutationOperator, weight: f64) {
+        match operator {
+            MutationOperator::TweakValue => self.tweak_value = weight,
+            MutationOperator::ChangeMode => self.change_mode = weight,
+            MutationOperator::ChangeOpcode => self.change_opcode = weight,
+            MutationOperator::ChangeModifier => self.change_modifier = weight,
+            MutationOperator::Insert => self.insert = weight,
+            MutationOperator::Delete => self.delete = weight,
+            MutationOperator::Duplicate => self.duplicate = weight,
+            MutationOperator::Swap => self.swap = weight,
+            MutationOperator::ShiftOrg => self.shift_org = weight,
+            MutationOperator::Replace => self.replace = weight,
+            MutationOperator::UseConstant => self.use_constant = weight,
+        }
+    }
+
+    /// Whether `operator` can change a warrior of `len` instructions.
+    fn applies(&self, operator: MutationOperator, len: usize) -> bool {
+        match operator {
+            MutationOperator::Insert => len < self.max_length,
+            MutationOperator::Duplicate => len > 0 && len < self.max_length,
+            MutationOperator::Delete | MutationOperator::Swap | MutationOperator::ShiftOrg => {
+                len > 1
+            }
+            MutationOperator::UseConstant => len > 0 && !self.constants.is_empty(),
+            _ => len > 0,
+        }
+    }
+
+    /// Applies one operator, picked by weight among those that can change
+    /// `warrior`. Returns the offspring and the operator, or `None` if no
+    /// enabled operator applies.
+    pub fn mutate(
+        &self,
+        warrior: &Warrior,
+        rng: &mut impl Rng,
+    ) -> Option<(Warrior, MutationOperator)> {
+        let len = warrior.body.len();
+        let candidates: Vec<(MutationOperator, f64)> = MutationOperator::ALL
+            .iter()
+            .map(|op| (*op, self.weight(*op)))
+            .filter(|(op, weight)| *weight > 0. && self.applies(*op, len))
+            .collect();
+
+        let total: f64 = candidates.iter().map(|(_, weight)| weight).sum();
+        if total <= 0. {
+            return None;
+        }
+
+        let mut pick = rng.gen_range(0. ..total);
+        let operator = candidates
+            .iter()
+            .find(|(_, weight)| {
+                let found = pick < *weight;
+                pick -= weight;
+                found
+            })
+            .map(|(op, _)| *op)
+            .unwrap_or(candidates[candidates.len() - 1].0);
+
+        let mut offspring = warrior.clone();
+        self.apply(&mut offspring, operator, rng);
+        Some((offspring, operator))
+    }
+
+    fn apply(&self, warrior: &mut Warrior, operator: MutationOperator, rng: &mut impl Rng) {
+        let len = warrior.body.len();
+        let i = rng.gen_range(0..len.max(1));
+
+        match operator {
+            MutationOperator::TweakValue => {
+                let field = &mut warrior.body[i].fields[rng.gen_range(0..2)];
+                let delta = rng.gen_range(1..=self.max_delta.max(1)) as isize;
+                let delta = if rng.gen_bool(0.5) { delta } else { -delta };
+                field.set_val(field.get_val().inc(delta));
+            }
+            MutationOperator::ChangeMode => {
+                let field = &mut warrior.body[i].fields[rng.gen_range(0..2)];
+                let val = *field.get_val();
+                let modes = modes_of(val);
+                let others: Vec<&Field> = modes.iter().filter(|m| *m != field).collect();
+                *field = *others[rng.gen_range(0..others.len())];
+            }
+            MutationOperator::ChangeOpcode => {
+                let instruction = &mut warrior.body[i];
+                let old = instruction.code;
+                while instruction.code == old {
+                    instruction.code = OpCode::get_random(rng);
+                }
+            }
+            MutationOperator::ChangeModifier => {
+                let instruction = &mut warrior.body[i];
+                let old = instruction.modifier;
+                while instruction.modifier == old {
+                    instruction.modifier = OpModifier::get_random(rng);
+                }
+            }
+            MutationOperator::Insert => {
+                let at = rng.gen_range(0..=len);
+                let instruction = self.random_instruction(len + 1, rng);
+                warrior.body.insert(at, instruction);
+                // keep starting on the same instruction
+                if at <= warrior.org && len > 0 {
+                    warrior.org += 1;
+                }
+            }
+            MutationOperator::Delete => {
+                warrior.body.remove(i);
+                if i < warrior.org {
+                    warrior.org -= 1;
+                }
+                warrior.org = warrior.org.min(len - 2);
+            }
+            MutationOperator::Duplicate => {
+                let instruction = warrior.body[i];
+                warrior.body.insert(i + 1, instruction);
+                if i < warrior.org {
+                    warrior.org += 1;
+                }
+            }
+            MutationOperator::Swap => {
+                let j = (i + rng.gen_range(1..len)) % len;
+                warrior.body.swap(i, j);
+            }
+            MutationOperator::ShiftOrg => {
+                warrior.org = (warrior.org + rng.gen_range(1..len)) % len;
+            }
+            MutationOperator::Replace => {
+                warrior.body[i] = self.random_instruction(len, rng);
+            }
+            MutationOperator::UseConstant => {
+                let field = &mut warrior.body[i].fields[rng.gen_range(0..2)];
+                let constant = self.constants[rng.gen_range(0..self.constants.len())];
+                field.set_val(ModUsize::new(constant as isize, self.core_size));
+            }
+        }
+    }
+
+    /// A random instruction pointing within a warrior of `len` instructions.
+    fn random_instruction(&self, len: usize, rng: &mut impl Rng) -> Instruction {
+        Instruction::get_random_with_rng(len.min(self.core_size), self.core_size, rng)
+    }
+}
+
+/// `val` under each of the eight addressing modes.
+fn modes_of(val: ModUsize) -> [Field; 8] {
+    use Decrement::*;
+    [
+        Field::Inmediate(val),
+        Field::Direct(val),
+        Field::AIndirect(val, None),
+        Field::AIndirect(val, Predecrement),
+        Field::AIndirect(val, Postincrement),
+        Field::BIndirect(val, None),
+        Field::BIndirect(val, Predecrement),
+        Field::BIndirect(val, Postincrement),
+    ]
+}
@@ -0,0 +1,153 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::utils::ModUsize;
+
+/// The point where the gaps a pattern leaves shrink: from `bombs` bombs on,
+/// no two neighbouring bombs are further apart than `largest_gap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stage {
+    pub bombs: usize,
+    pub largest_gap: usize,
+    pub smallest_gap: usize,
+}
+
+/// How a bomber or scanner stepping `step` cells at a time covers the core,
+/// starting next to itself at cell 0. A scanner looks at the same cells a
+/// bomber drops bombs on, in the same order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pattern {
+    pub core_size: usize,
+    pub step: usize,
+    /// The pattern only ever lands on multiples of this, a mod-N pattern.
+    pub modulus: usize,
+    /// Every cell bombed, in order, until the pattern wraps around to 0.
+    pub fill_order: Vec<usize>,
+    pub stages: Vec<Stage>,
+}
+
+impl Pattern {
+    pub fn new(core_size: usize, step: usize) -> Self {
+        // cell 0 is where the warrior is, so it counts as covered already
+        let mut cells = BTreeSet::from([0]);
+        let mut gaps = BTreeMap::from([(core_size, 1)]);
+        let mut fill_order = vec![];
+        let mut stages = vec![];
+
+        let mut position = ModUsize::new(step as isize, core_size);
+        while position.val != 0 {
+            let cell = position.val;
+            let before = cells.range(..cell).next_back().copied().unwrap_or(0);
+            let after = cells.range(cell..).next().copied().unwrap_or(core_size);
+
+            // the bomb splits the gap it lands in in two
+            let split = after - before;
+            match gaps.get_mut(&split) {
+                Some(1) => {
+                    gaps.remove(&split);
+                }
+                Some(count) => *count -= 1,
+                None => unreachable!("{split} isn't a gap"),
+            }
+            *gaps.entry(cell - before).or_insert(0) += 1;
+            *gaps.entry(after - cell).or_insert(0) += 1;
+
+            cells.insert(cell);
+            fill_order.push(cell);
+
+            let largest_gap = *gaps.keys().next_back().unwrap();
+            if stages
+                .last()
+                .is_none_or(|s: &Stage| largest_gap < s.largest_gap)
+            {
+                stages.push(Stage {
+                    bombs: fill_order.len(),
+                    largest_gap,
+                    smallest_gap: *gaps.keys().next().unwrap(),
+                });
+            }
+
+            position = position + step;
+        }
+
+        Self {
+            core_size,
+            step,
+            modulus: core_size / (fill_order.len() + 1),
+            fill_order,
+            stages,
+        }
+    }
+
+    /// Bombs dropped until every warrior at least `length` long has been
+    /// hit, `None` if the pattern never gets there.
+    pub fn bombs_to_cover(&self, length: usize) -> Option<usize> {
+        // a gap of `length` leaves `length - 1` cells in between untouched
+        self.stages
+            .iter()
+            .find(|s| s.largest_gap <= length)
+            .map(|s| s.bombs)
+    }
+
+    /// Cycles a bomber dropping one bomb every `cycles_per_bomb` cycles
+    /// takes to hit every warrior at least `length` long.
+    pub fn cycles_to_cover(&self, length: usize, cycles_per_bomb: usize) -> Option<usize> {
+        self.bombs_to_cover(length)
+            .map(|bombs| bombs * cycles_per_bomb)
+    }
+
+    /// How far the pattern is from spreading bombs perfectly evenly, summed
+    /// over every bomb until it covers warriors of `length`. 0 is perfect.
+    pub fn unevenness(&self, length: usize) -> usize {
+        let bombs = self.bombs_to_cover(length).unwrap_or(self.fill_order.len());
+        let mut stage = 0;
+        (1..=bombs)
+            .map(|b| {
+                while self.stages.get(stage + 1).is_some_and(|s| s.bombs <= b) {
+                    stage += 1;
+                }
+                let even = self.core_size.div_ceil(b + 1);
+                self.stages[stage].largest_gap.saturating_sub(even)
+            })
+            .sum()
+    }
+
+    /// The step as an EQU line for the assembler.
+    pub fn to_equ(&self, name: &str) -> String {
+        format!(
+            "{name} EQU {} ; mod {}, {} bombs",
+            self.step,
+            self.modulus,
+            self.fill_order.len()
+        )
+    }
+}
+
+/// The best steps for bombing or scanning warriors at least `length` long
+/// in a core of `core_size`, best first: fewest bombs until every such
+/// warrior is hit, then the most even spread on the way. Steps past half
+/// the core mirror those below it and are left out.
+pub fn optima(core_size: usize, length: usize) -> Vec<Pattern> {
+    let mut patterns: Vec<(usize, usize, Pattern)> = (1..=core_size / 2)
+        .filter(|step| gcd(*step, core_size) <= length)
+        .map(|step| Pattern::new(core_size, step))
+        .filter_map(|pattern| {
+            let bombs = pattern.bombs_to_cover(length)?;
+            Some((bombs, pattern.unevenness(length), pattern))
+        })
+        .collect();
+
+    patterns.sort_by_key(|(bombs, unevenness, pattern)| (*bombs, *unevenness, pattern.step));
+    patterns
+        .into_iter()
+        .map(|(_, _, pattern)| pattern)
+        .collect()
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
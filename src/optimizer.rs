@@ -0,0 +1,307 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{assembler::evaluate_with, batch::Batch, benchmark::Benchmark, warrior::Warrior};
+
+/// An EQU constant to tune, declared in the source by a comment like
+/// `;opt step 1..4000`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Parameter {
+    pub name: String,
+    pub min: isize,
+    pub max: isize,
+    /// What the source sets it to, the hill climb starts from here.
+    pub initial: isize,
+}
+
+impl Parameter {
+    /// Every `;opt` parameter of `source`, in order. Each has to name an EQU.
+    pub fn parse_all(source: &str) -> Result<Vec<Parameter>, String> {
+        let mut parameters = vec![];
+
+        for (i, line) in source.lines().enumerate() {
+            let Some(comment) = line.find(';').map(|at| line[at + 1..].trim()) else {
+                continue;
+            };
+            let mut words = comment.split_whitespace();
+            if words.next().map(str::to_lowercase).as_deref() != Some("opt") {
+                continue;
+            }
+
+            let bad = || format!("linea {i}: expected \";opt name min..max\", found \"{line}\"");
+            let name = words.next().ok_or_else(bad)?.to_string();
+            let (min, max) = words
+                .next()
+                .and_then(|r| r.split_once(".."))
+                .ok_or_else(bad)?;
+            let (min, max): (isize, isize) = (
+                min.parse().map_err(|_| bad())?,
+                max.parse().map_err(|_| bad())?,
+            );
+            if min > max {
+                return Err(format!("linea {i}: {name} has an empty range {min}..{max}"));
+            }
+
+            let value = equ_value(source, &name)
+                .ok_or_else(|| format!("linea {i}: {name} isn't defined by an EQU"))?;
+            let initial = evaluate_with(&value, &mut |_| Err(String::new()))
+                .unwrap_or(min + (max - min) / 2)
+                .clamp(min, max);
+
+            parameters.push(Parameter {
+                name,
+                min,
+                max,
+                initial,
+            });
+        }
+
+        Ok(parameters)
+    }
+}
+
+/// Splits a line into the name its EQU defines, if it's one, and the
+/// comment it ends with.
+fn split_equ(line: &str) -> (Option<&str>, &str) {
+    let (code, comment) = line.split_at(line.find(';').unwrap_or(line.len()));
+    let mut words = code.split_whitespace();
+    match (words.next(), words.next()) {
+        (Some(name), Some(equ)) if equ.eq_ignore_ascii_case("equ") => {
+            (Some(name.trim_end_matches(':')), comment)
+        }
+        _ => (None, comment),
+    }
+}
+
+/// The text `name` is defined as.
+fn equ_value(source: &str, name: &str) -> Option<String> {
+    source.lines().find_map(|line| match split_equ(line) {
+        (Some(equ), comment) if equ.eq_ignore_ascii_case(name) => {
+            let code = &line[..line.len() - comment.len()];
+            let at = code.to_uppercase().find("EQU")? + 3;
+            Some(code[at..].trim().to_string())
+        }
+        _ => None,
+    })
+}
+
+/// `source` with every parameter's EQU set to the matching value.
+pub fn instantiate(source: &str, parameters: &[Parameter], values: &[isize]) -> String {
+    let mut instantiated = String::new();
+
+    for line in source.lines() {
+        let (equ, comment) = split_equ(line);
+        let value = equ.and_then(|equ| {
+            parameters
+                .iter()
+                .zip(values)
+                .find(|(p, _)| p.name.eq_ignore_ascii_case(equ))
+        });
+
+        match value {
+            Some((parameter, value)) => {
+                instantiated.push_str(&format!("{} EQU {value} {comment}", parameter.name))
+            }
+            None => instantiated.push_str(line),
+        }
+        instantiated.push('\n');
+    }
+
+    instantiated
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Search {
+    /// This many evenly spaced values of every parameter, in every
+    /// combination.
+    Grid(usize),
+    /// This many combinations picked at random.
+    Random(usize),
+    /// This many steps from the source's values, each changing one parameter
+    /// and kept if it scores better. Steps start at a quarter of the range
+    /// and shrink down to 1.
+    HillClimb(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trial {
+    pub values: Vec<isize>,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Optimization {
+    pub parameters: Vec<Parameter>,
+    /// Every combination played, best first.
+    pub trials: Vec<Trial>,
+    /// The source with the best combination filled in.
+    pub source: String,
+}
+
+impl Optimization {
+    pub fn best(&self) -> Option<&Trial> {
+        self.trials.first()
+    }
+}
+
+impl fmt::Display for Optimization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for parameter in self.parameters.iter() {
+            write!(f, "{:>10} ", parameter.name)?;
+        }
+        writeln!(f, "{:>8}", "score")?;
+
+        for trial in self.trials.iter() {
+            for value in trial.values.iter() {
+                write!(f, "{value:>10} ")?;
+            }
+            writeln!(f, "{:>8.2}", trial.score)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Tunes the `;opt` constants of a warrior against a benchmark.
+pub struct Optimizer {
+    pub search: Search,
+    pub benchmark: Benchmark,
+    pub batch: Batch,
+    pub seed: Option<u64>,
+}
+
+impl Optimizer {
+    pub fn new(benchmark: Benchmark, batch: Batch) -> Self {
+        Self {
+            search: Search::Random(100),
+            benchmark,
+            batch,
+            seed: None,
+        }
+    }
+
+    pub fn optimize(&self, source: &str) -> Result<Optimization, String> {
+        let parameters = Parameter::parse_all(source)?;
+        if parameters.is_empty() {
+            return Err("no ;opt parameters to optimize".into());
+        }
+
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let mut scores = HashMap::new();
+
+        match self.search {
+            Search::Grid(steps) => {
+                let mut combinations = vec![vec![]];
+                for parameter in parameters.iter() {
+                    let values = grid(parameter, steps);
+                    combinations = combinations
+                        .into_iter()
+                        .flat_map(|c: Vec<isize>| {
+                            values.iter().map(move |v| [c.clone(), vec![*v]].concat())
+                        })
+                        .collect();
+                }
+                self.play(source, &parameters, combinations, &mut scores)?;
+            }
+            Search::Random(samples) => {
+                let combinations = (0..samples)
+                    .map(|_| {
+                        parameters
+                            .iter()
+                            .map(|p| rng.gen_range(p.min..=p.max))
+                            .collect()
+                    })
+                    .collect();
+                self.play(source, &parameters, combinations, &mut scores)?;
+            }
+            Search::HillClimb(steps) => {
+                let mut current: Vec<isize> = parameters.iter().map(|p| p.initial).collect();
+                self.play(source, &parameters, vec![current.clone()], &mut scores)?;
+
+                for step in 0..steps {
+                    let i = rng.gen_range(0..parameters.len());
+                    let parameter = &parameters[i];
+                    let left = 1. - step as f64 / steps as f64;
+                    let radius = ((parameter.max - parameter.min) as f64 * left / 4.).ceil();
+                    let radius = (radius as isize).max(1);
+
+                    let mut neighbour = current.clone();
+                    let delta = rng.gen_range(1..=radius) * if rng.gen_bool(0.5) { 1 } else { -1 };
+                    neighbour[i] = (current[i] + delta).clamp(parameter.min, parameter.max);
+
+                    self.play(source, &parameters, vec![neighbour.clone()], &mut scores)?;
+                    if scores[&neighbour] > scores[&current] {
+                        current = neighbour;
+                    }
+                }
+            }
+        }
+
+        let mut trials: Vec<Trial> = scores
+            .into_iter()
+            .map(|(values, score)| Trial { values, score })
+            .collect();
+        trials.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.values.cmp(&b.values)));
+
+        let best = trials.first().ok_or("the search played no combinations")?;
+        let source = instantiate(source, &parameters, &best.values);
+        Ok(Optimization {
+            parameters,
+            trials,
+            source,
+        })
+    }
+
+    /// Scores every combination not played yet, all in one batch.
+    fn play(
+        &self,
+        source: &str,
+        parameters: &[Parameter],
+        mut combinations: Vec<Vec<isize>>,
+        scores: &mut HashMap<Vec<isize>, f64>,
+    ) -> Result<(), String> {
+        combinations.retain(|c| !scores.contains_key(c));
+        combinations.sort();
+        combinations.dedup();
+        if combinations.is_empty() {
+            return Ok(());
+        }
+
+        let name = Warrior::declared_name(source).unwrap_or_else(|| "optimized".into());
+        let core_size = self.batch.config.core_size;
+        let warriors = combinations
+            .iter()
+            .map(|c| Warrior::parse(instantiate(source, parameters, c), name.clone(), core_size))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let results = self.benchmark.score(&warriors, &self.batch)?;
+        scores.extend(combinations.into_iter().zip(results));
+        Ok(())
+    }
+}
+
+/// `steps` values evenly spread over the parameter's range, or every value
+/// if there are fewer.
+fn grid(parameter: &Parameter, steps: usize) -> Vec<isize> {
+    let span = (parameter.max - parameter.min) as usize;
+    if steps > span {
+        return (parameter.min..=parameter.max).collect();
+    }
+
+    let steps = steps.max(1);
+    (0..steps)
+        .map(|k| match steps {
+            1 => parameter.min,
+            _ => parameter.min + (span * k / (steps - 1)) as isize,
+        })
+        .collect()
+}
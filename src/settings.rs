@@ -0,0 +1,125 @@
+use toml_edit::{Array, DocumentMut, Item, Value};
+
+/// A table of a TOML config file, with getters that check the type of a
+/// key and name it in their errors. Parsing is left to toml_edit.
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    /// Dotted name of the table, empty for the root, to point errors at.
+    pub name: String,
+    entries: toml_edit::Table,
+}
+
+pub fn parse(source: &str) -> Result<Table, String> {
+    let document: DocumentMut = source.parse().map_err(|err| format!("{err}"))?;
+    Ok(Table {
+        name: String::new(),
+        entries: document.as_table().clone(),
+    })
+}
+
+fn type_name(item: &Item) -> &'static str {
+    match item {
+        Item::None => "nothing",
+        Item::Value(Value::String(_)) => "a string",
+        Item::Value(Value::Integer(_)) => "an integer",
+        Item::Value(Value::Float(_)) => "a float",
+        Item::Value(Value::Boolean(_)) => "a boolean",
+        Item::Value(Value::Datetime(_)) => "a date",
+        Item::Value(Value::Array(_)) => "an array",
+        Item::Value(Value::InlineTable(_)) | Item::Table(_) => "a table",
+        Item::ArrayOfTables(_) => "an array of tables",
+    }
+}
+
+impl Table {
+    pub fn get(&self, key: &str) -> Option<&Item> {
+        self.entries.get(key)
+    }
+
+    fn path(&self, key: &str) -> String {
+        match self.name.as_str() {
+            "" => key.to_string(),
+            name => format!("{name}.{key}"),
+        }
+    }
+
+    /// What `key` holds, through `read`, or an error naming it if that
+    /// isn't `expected`.
+    fn typed<'a, T>(
+        &'a self,
+        key: &str,
+        expected: &str,
+        read: impl Fn(&'a Item) -> Option<T>,
+    ) -> Result<Option<T>, String> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(item) => read(item).map(Some).ok_or_else(|| {
+                format!(
+                    "{} should be {expected}, not {}",
+                    self.path(key),
+                    type_name(item)
+                )
+            }),
+        }
+    }
+
+    pub fn string(&self, key: &str) -> Result<Option<&str>, String> {
+        self.typed(key, "a string", Item::as_str)
+    }
+
+    pub fn integer(&self, key: &str) -> Result<Option<i64>, String> {
+        self.typed(key, "an integer", Item::as_integer)
+    }
+
+    pub fn usize(&self, key: &str) -> Result<Option<usize>, String> {
+        match self.integer(key)? {
+            None => Ok(None),
+            Some(i) => usize::try_from(i)
+                .map(Some)
+                .map_err(|_| format!("{} can't be negative", self.path(key))),
+        }
+    }
+
+    /// Integers are taken as floats too.
+    pub fn float(&self, key: &str) -> Result<Option<f64>, String> {
+        self.typed(key, "a number", |item| {
+            item.as_float().or(item.as_integer().map(|i| i as f64))
+        })
+    }
+
+    pub fn boolean(&self, key: &str) -> Result<Option<bool>, String> {
+        self.typed(key, "a boolean", Item::as_bool)
+    }
+
+    pub fn array(&self, key: &str) -> Result<Option<&Array>, String> {
+        self.typed(key, "an array", Item::as_array)
+    }
+
+    /// A `[table]` or an inline one.
+    pub fn table(&self, key: &str) -> Result<Option<Table>, String> {
+        self.typed(key, "a table", |item| match item {
+            Item::Table(table) => Some(table.clone()),
+            Item::Value(Value::InlineTable(table)) => Some(table.clone().into_table()),
+            _ => None,
+        })
+        .map(|entries| {
+            entries.map(|entries| Table {
+                name: self.path(key),
+                entries,
+            })
+        })
+    }
+
+    /// Fails on any key not in `known`, so a typo doesn't go unnoticed.
+    pub fn expect_only(&self, known: &[&str]) -> Result<(), String> {
+        match self
+            .entries
+            .iter()
+            .map(|(key, _)| key)
+            .find(|key| !known.contains(key))
+        {
+            Some(key) => Err(format!("unknown key {}", self.path(key))),
+            None => Ok(()),
+        }
+    }
+}
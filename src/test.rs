@@ -1,13 +1,39 @@
 pub mod test_chang_vs_mice;
 pub mod test_death;
+pub mod differential;
+pub mod reference;
 mod test_dwarf;
 pub mod test_imp;
 mod test_imp_wall;
 pub mod test_predecrement;
+mod test_process_queue;
 mod test_warrior_colision;
 mod test_arithmetic;
+mod test_assembler;
+mod test_batch;
+mod test_benchmark;
+mod test_cell;
+mod test_checkpoint;
+mod test_cli;
+mod test_crossover;
+mod test_differential;
+mod test_environment;
+mod test_evolution;
+mod test_hall_of_fame;
+mod test_fuzz;
+mod test_hill;
+mod test_islands;
+mod test_lineage;
+mod test_map_elites;
+mod test_minimizer;
+mod test_optima;
+mod test_optimizer;
+mod test_serde;
+mod test_settings;
+mod test_tournament;
 pub mod test_div_cero;
 mod test_mod_cero;
+mod test_mutation;
 
 #[cfg(test)]
 mod tests {
@@ -43,7 +69,7 @@ mod tests {
         let res = parse_ares_dump(file_path, runtime.core_size);
 
         for cell_i in 0..runtime.core_size {
-            let a = <Instruction as Into<ReadOnlyInstruction>>::into(runtime.core[cell_i].clone());
+            let a = <Instruction as Into<ReadOnlyInstruction>>::into(runtime.core[cell_i].to_instruction(runtime.core_size));
             let b = res[cell_i];
             if a != b {
                 runtime.print_state(Some(cell_i.max(10) - 10..cell_i + 10));
@@ -0,0 +1,322 @@
+#[cfg(test)]
+pub mod harness {
+    use std::{collections::BTreeMap, fmt::Write, fs};
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        core::{CoreConfig, CoreRuntime},
+        instruction::{cell::Cell, instruction::Instruction},
+        test::reference::model::{self, ReferenceMars},
+        utils::ModUsize,
+        warrior::Warrior,
+    };
+
+    /*
+        Reference traces are plain text files:
+
+            SOURCE <what recorded the trace>
+            CORESIZE 8000
+            MAXPROCESSES 8000          (optional)
+            SEED 5                     (optional)
+            WARRIOR <path to .red> [load address]
+            CYCLE <ticks executed>
+            <address> <OPCODE.MODIFIER> <A field>, <B field>
+            QUEUE <warrior> <address> <address> ...
+
+        Warriors without a load address are placed the way `CoreConfig`
+        places them, drawing from a StdRng seeded with SEED.
+
+        There is a CYCLE section for every tick from 0 on, and the whole core
+        and every queue are compared at each of them. To keep traces small a
+        section lists only the cells that changed since the one before, the
+        first one every cell that isn't DAT.F $0, $0. Every warrior still
+        alive has its QUEUE listed in every section, by its position among
+        the WARRIOR lines and head of the queue first.
+
+        The format is this harness's own, nothing here reads pMARS output.
+        The traces in src/test/traces are all recorded by `record` from the
+        reference model in `test::reference`, as their SOURCE line says, so
+        they check the runtime against that model, not against pMARS. Both
+        were written from the same reading of the standard, and a mistake
+        shared by the two goes unnoticed.
+    */
+
+    pub struct Snapshot {
+        pub cycle: usize,
+        /// Cells that changed since the previous snapshot.
+        pub cells: Vec<(usize, Instruction)>,
+        pub queues: Vec<(usize, Vec<usize>)>,
+    }
+
+    pub struct Trace {
+        pub source: String,
+        pub core_size: usize,
+        pub max_processes: Option<usize>,
+        pub seed: Option<u64>,
+        pub warriors: Vec<(String, Option<usize>)>,
+        pub snapshots: Vec<Snapshot>,
+    }
+
+    impl Trace {
+        pub fn load(file_path: &str) -> Result<Self, String> {
+            let contents = fs::read_to_string(file_path)
+                .map_err(|err| format!("reading trace {file_path} failed: {err}"))?;
+            Self::parse(&contents).map_err(|err| format!("{file_path}: {err}"))
+        }
+
+        pub fn parse(contents: &str) -> Result<Self, String> {
+            let mut source = None;
+            let mut core_size = None;
+            let mut max_processes = None;
+            let mut seed = None;
+            let mut warriors = vec![];
+            let mut snapshots: Vec<Snapshot> = vec![];
+
+            for (i, line) in contents.lines().enumerate() {
+                let line = match line.find(';') {
+                    Some(x) => &line[0..x],
+                    None => line,
+                }
+                .trim();
+
+                if line.is_empty() {
+                    continue;
+                }
+
+                let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+                let rest = rest.trim();
+                let parse_num = |s: &str| {
+                    s.parse::<usize>()
+                        .map_err(|_| format!("line {i}: \"{s}\" is not a number"))
+                };
+
+                match keyword {
+                    "SOURCE" => source = Some(rest.to_string()),
+                    "CORESIZE" => core_size = Some(parse_num(rest)?),
+                    "MAXPROCESSES" => max_processes = Some(parse_num(rest)?),
+                    "SEED" => seed = Some(parse_num(rest)? as u64),
+                    "WARRIOR" => {
+                        let mut words = rest.split_whitespace();
+                        let path = words
+                            .next()
+                            .ok_or(format!("line {i}: WARRIOR needs a path"))?;
+                        let position = words.next().map(parse_num).transpose()?;
+                        warriors.push((path.to_string(), position));
+                    }
+                    "CYCLE" => {
+                        let cycle = parse_num(rest)?;
+                        if cycle != snapshots.len() {
+                            return Err(format!(
+                                "line {i}: expected CYCLE {}, every tick needs one",
+                                snapshots.len()
+                            ));
+                        }
+                        snapshots.push(Snapshot {
+                            cycle,
+                            cells: vec![],
+                            queues: vec![],
+                        });
+                    }
+                    "QUEUE" => {
+                        let snapshot = snapshots
+                            .last_mut()
+                            .ok_or(format!("line {i}: QUEUE outside of a CYCLE"))?;
+                        let mut words = rest.split_whitespace().map(parse_num);
+                        let warrior = words
+                            .next()
+                            .ok_or(format!("line {i}: QUEUE needs a warrior"))??;
+                        let queue = words.collect::<Result<Vec<_>, _>>()?;
+                        snapshot.queues.push((warrior, queue));
+                    }
+                    address => {
+                        let core_size =
+                            core_size.ok_or(format!("line {i}: CORESIZE must come first"))?;
+                        let snapshot = snapshots
+                            .last_mut()
+                            .ok_or(format!("line {i}: cell outside of a CYCLE"))?;
+                        let instruction = Instruction::parse(rest.to_uppercase(), core_size)
+                            .map_err(|err| format!("line {i}: {err}"))?
+                            .ok_or(format!("line {i}: missing instruction"))?;
+                        snapshot.cells.push((parse_num(address)?, instruction));
+                    }
+                }
+            }
+
+            Ok(Self {
+                source: source.ok_or("trace has no SOURCE")?,
+                core_size: core_size.ok_or("trace has no CORESIZE")?,
+                max_processes,
+                seed,
+                warriors,
+                snapshots,
+            })
+        }
+
+        /// Every cell as the trace has it after `cycle` ticks.
+        pub fn core_at(&self, cycle: usize) -> Vec<Cell> {
+            let mut core = vec![Cell::EMPTY; self.core_size];
+            for snapshot in &self.snapshots[..=cycle] {
+                for (address, cell) in snapshot.cells.iter() {
+                    core[*address % self.core_size] = Cell::from_instruction(cell, self.core_size);
+                }
+            }
+            core
+        }
+
+        /// The core the trace starts from, with every warrior loaded.
+        pub fn core_config(&self) -> Result<CoreConfig, String> {
+            let mut core_conf = CoreConfig::new(self.core_size);
+            if let Some(max_processes) = self.max_processes {
+                core_conf.set_max_processes(max_processes);
+            }
+
+            let mut rng = StdRng::seed_from_u64(self.seed.unwrap_or(0));
+            for (path, position) in self.warriors.iter() {
+                let warrior = Warrior::load(path, self.core_size)?;
+                let position = position.map(|p| ModUsize::new(p as isize, self.core_size));
+                core_conf.deploy_with_rng(warrior, position, &mut rng)?;
+            }
+            Ok(core_conf)
+        }
+    }
+
+    fn cell_to_string(cell: &Instruction) -> String {
+        format!(
+            "{}.{} {}, {}",
+            cell.code,
+            cell.get_modifier().to_string().trim_start_matches('.'),
+            cell.fields[0],
+            cell.fields[1]
+        )
+    }
+
+    /// Compares every cell and process queue of `runtime` with `expected`,
+    /// the whole core as the trace has it so far, returning a diff of what
+    /// diverges.
+    pub fn diff_snapshot(
+        runtime: &CoreRuntime,
+        expected: &[Cell],
+        queues: &[(usize, Vec<usize>)],
+    ) -> Option<String> {
+        let mut report = String::new();
+        let m = runtime.core_size;
+
+        for (address, (actual, expected)) in runtime.core.iter().zip(expected).enumerate() {
+            if !actual.same_instruction(expected) {
+                let expected = cell_to_string(&expected.to_instruction(m));
+                let actual = cell_to_string(&actual.to_instruction(m));
+                let _ = writeln!(report, "  {address:04} - {expected}");
+                let _ = writeln!(report, "       + {actual}");
+            }
+        }
+
+        let actual: Vec<(usize, Vec<usize>)> = runtime
+            .warriors
+            .iter()
+            .filter(|w| w.thread_count() > 0)
+            .map(|w| {
+                let queue = w.get_counters().iter().map(|ic| ic.val).collect();
+                (w.deploy_index, queue)
+            })
+            .collect();
+        let mut sorted = actual.clone();
+        sorted.sort();
+        let mut queues = queues.to_vec();
+        queues.sort();
+
+        if sorted != queues {
+            let _ = writeln!(report, "  queues - {queues:?}");
+            let _ = writeln!(report, "  queues + {sorted:?}");
+        }
+
+        if report.is_empty() {
+            None
+        } else {
+            Some(report)
+        }
+    }
+
+    /// Replays the battle described by the trace and checks the runtime
+    /// against it after every tick.
+    pub fn run_differential(trace_path: &str) -> Result<(), String> {
+        let trace = Trace::load(trace_path)?;
+        let mut runtime = trace.core_config()?.brawl();
+        let mut expected = vec![Cell::EMPTY; trace.core_size];
+
+        for snapshot in trace.snapshots.iter() {
+            if snapshot.cycle > 0 {
+                runtime.tick();
+            }
+            for (address, cell) in snapshot.cells.iter() {
+                expected[*address % trace.core_size] =
+                    Cell::from_instruction(cell, trace.core_size);
+            }
+
+            if let Some(report) = diff_snapshot(&runtime, &expected, &snapshot.queues) {
+                return Err(format!(
+                    "{trace_path} ({}): divergence at cycle {}\n{report}",
+                    trace.source, snapshot.cycle
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes a trace of `cycles` ticks of the battle `header` sets up, as
+    /// played by the reference model rather than the runtime. Its SOURCE line
+    /// says so.
+    pub fn record(header: &str, cycles: usize) -> Result<String, String> {
+        let mut trace = format!("SOURCE reference model, test::reference\n{header}");
+        let start = Trace::parse(&trace)?;
+        let runtime = start.core_config()?.brawl();
+        let mut reference = ReferenceMars::from_runtime(&runtime);
+        // queues are told apart by load order
+        for (i, (name, _)) in reference.warriors.iter_mut().enumerate() {
+            *name = i.to_string();
+        }
+
+        let m = start.core_size;
+        let empty = model::Cell::from_instruction(&Cell::EMPTY.to_instruction(m));
+        let mut previous = vec![empty; m];
+
+        for cycle in 0..=cycles {
+            if cycle > 0 {
+                reference.step();
+            }
+            let _ = writeln!(trace, "\nCYCLE {cycle}");
+            for (address, cell) in reference.core.iter().enumerate() {
+                if *cell == previous[address] {
+                    continue;
+                }
+                previous[address] = *cell;
+                let text = format!(
+                    "{}.{} {}{}, {}{}",
+                    cell.code,
+                    cell.modifier.to_string().trim_start_matches('.'),
+                    cell.a_mode,
+                    cell.a,
+                    cell.b_mode,
+                    cell.b
+                );
+                let cell = Instruction::parse(text, m)?.ok_or("unprintable cell")?;
+                let _ = writeln!(trace, "{address:04} {}", cell_to_string(&cell));
+            }
+            let queues: BTreeMap<usize, Vec<String>> = reference
+                .warriors
+                .iter()
+                .filter(|(_, queue)| !queue.is_empty())
+                .map(|(i, queue)| {
+                    let queue = queue.iter().map(|pc| pc.to_string()).collect();
+                    (i.parse().unwrap(), queue)
+                })
+                .collect();
+            for (i, queue) in queues {
+                let _ = writeln!(trace, "QUEUE {i} {}", queue.join(" "));
+            }
+        }
+
+        Ok(trace)
+    }
+}
@@ -0,0 +1,317 @@
+#[cfg(test)]
+pub mod model {
+    /*
+        A deliberately naive ICWS '94 interpreter, written straight from the
+        EMI94 pseudocode in the standard draft. It shares nothing with
+        `CoreRuntime` but the opcode and modifier enums, so the two can be
+        checked against each other.
+    */
+
+    use std::collections::VecDeque;
+
+    use crate::{
+        core::CoreRuntime,
+        instruction::{
+            decrement::Decrement, field::Field, instruction::Instruction, op_code::OpCode,
+            op_modifier::OpModifier,
+        },
+    };
+
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub struct Cell {
+        pub code: OpCode,
+        pub modifier: OpModifier,
+        pub a_mode: char,
+        pub a: usize,
+        pub b_mode: char,
+        pub b: usize,
+    }
+
+    fn mode_of(field: &Field) -> (char, usize) {
+        match *field {
+            Field::Inmediate(x) => ('#', x.val),
+            Field::Direct(x) => ('$', x.val),
+            Field::AIndirect(x, Decrement::None) => ('*', x.val),
+            Field::AIndirect(x, Decrement::Predecrement) => ('{', x.val),
+            Field::AIndirect(x, Decrement::Postincrement) => ('}', x.val),
+            Field::BIndirect(x, Decrement::None) => ('@', x.val),
+            Field::BIndirect(x, Decrement::Predecrement) => ('<', x.val),
+            Field::BIndirect(x, Decrement::Postincrement) => ('>', x.val),
+        }
+    }
+
+    // Section 2.2 of the draft, "Default modifiers".
+    fn default_modifier(code: OpCode, a_mode: char, b_mode: char) -> OpModifier {
+        use OpCode::*;
+        match code {
+            DAT | NOP => OpModifier::F,
+            MOV | SEQ | SNE | CMP if a_mode == '#' => OpModifier::AB,
+            MOV | SEQ | SNE | CMP if b_mode == '#' => OpModifier::B,
+            MOV | SEQ | SNE | CMP => OpModifier::I,
+            ADD | SUB | MUL | DIV | MOD if a_mode == '#' => OpModifier::AB,
+            ADD | SUB | MUL | DIV | MOD if b_mode == '#' => OpModifier::B,
+            ADD | SUB | MUL | DIV | MOD => OpModifier::F,
+            SLT if a_mode == '#' => OpModifier::AB,
+            SLT => OpModifier::B,
+            JMP | JMZ | JMN | DJN | SPL => OpModifier::B,
+        }
+    }
+
+    impl Cell {
+        pub fn from_instruction(instruction: &Instruction) -> Self {
+            let (a_mode, a) = mode_of(&instruction.fields[0]);
+            let (b_mode, b) = mode_of(&instruction.fields[1]);
+
+            let modifier = match instruction.modifier {
+                OpModifier::Default => default_modifier(instruction.code, a_mode, b_mode),
+                modifier => modifier,
+            };
+
+            Self {
+                code: instruction.code,
+                modifier,
+                a_mode,
+                a,
+                b_mode,
+                b,
+            }
+        }
+    }
+
+    pub struct ReferenceMars {
+        pub m: usize,
+        pub max_processes: usize,
+        pub core: Vec<Cell>,
+        pub warriors: Vec<(String, VecDeque<usize>)>,
+    }
+
+    impl ReferenceMars {
+        pub fn from_runtime(runtime: &CoreRuntime) -> Self {
+            Self {
+                m: runtime.core_size,
+                max_processes: runtime.max_processes,
+                core: runtime
+                    .core
+                    .iter()
+                    .map(|cell| Cell::from_instruction(&cell.to_instruction(runtime.core_size)))
+                    .collect(),
+                warriors: runtime
+                    .warriors
+                    .iter()
+                    .map(|w| {
+                        (
+                            w.name.clone(),
+                            w.get_counters().iter().map(|ic| ic.val).collect(),
+                        )
+                    })
+                    .collect(),
+            }
+        }
+
+        /// Evaluates one operand, returning the pointer it resolves to and a
+        /// copy of the instruction there taken before any postincrement.
+        fn operand(&mut self, pc: usize, mode: char, value: usize) -> (usize, Cell) {
+            let m = self.m;
+
+            if mode == '#' {
+                return (pc, self.core[pc]);
+            }
+
+            let mut pointer = (pc + value) % m;
+
+            if mode == '$' {
+                return (pointer, self.core[pointer]);
+            }
+
+            let indirect = pointer;
+
+            match mode {
+                '{' => self.core[indirect].a = (self.core[indirect].a + m - 1) % m,
+                '<' => self.core[indirect].b = (self.core[indirect].b + m - 1) % m,
+                _ => (),
+            }
+
+            pointer = match mode {
+                '*' | '{' | '}' => (pointer + self.core[indirect].a) % m,
+                _ => (pointer + self.core[indirect].b) % m,
+            };
+
+            let copy = self.core[pointer];
+
+            match mode {
+                '}' => self.core[indirect].a = (self.core[indirect].a + 1) % m,
+                '>' => self.core[indirect].b = (self.core[indirect].b + 1) % m,
+                _ => (),
+            }
+
+            (pointer, copy)
+        }
+
+        pub fn step(&mut self) {
+            let pc = loop {
+                match self.warriors.first_mut() {
+                    None => return,
+                    Some((_, queue)) => match queue.pop_front() {
+                        Some(pc) => break pc,
+                        None => {
+                            self.warriors.remove(0);
+                        }
+                    },
+                }
+            };
+
+            let m = self.m;
+            let ir = self.core[pc];
+            let (rpa, ira) = self.operand(pc, ir.a_mode, ir.a);
+            let (wpb, irb) = self.operand(pc, ir.b_mode, ir.b);
+
+            let next = (pc + 1) % m;
+            let skip = (pc + 2) % m;
+
+            // values of the two operands as (A, B) pairs
+            let (aa, ab) = (ira.a, ira.b);
+            let (ba, bb) = (irb.a, irb.b);
+
+            let mut queue: Vec<usize> = vec![];
+
+            use OpModifier::*;
+
+            match ir.code {
+                OpCode::DAT => (),
+                OpCode::NOP => queue.push(next),
+                OpCode::MOV => {
+                    match ir.modifier {
+                        A => self.core[wpb].a = aa,
+                        B => self.core[wpb].b = ab,
+                        AB => self.core[wpb].b = aa,
+                        BA => self.core[wpb].a = ab,
+                        F | Default => {
+                            self.core[wpb].a = aa;
+                            self.core[wpb].b = ab;
+                        }
+                        X => {
+                            self.core[wpb].b = aa;
+                            self.core[wpb].a = ab;
+                        }
+                        I => self.core[wpb] = ira,
+                    }
+                    queue.push(next);
+                }
+                OpCode::ADD | OpCode::SUB | OpCode::MUL | OpCode::DIV | OpCode::MOD => {
+                    // returns None on a division by zero
+                    let op = |b: usize, a: usize| -> Option<usize> {
+                        match ir.code {
+                            OpCode::ADD => Some((b + a) % m),
+                            OpCode::SUB => Some((b + m - a) % m),
+                            OpCode::MUL => Some((b * a) % m),
+                            OpCode::DIV => (a != 0).then(|| b / a),
+                            _ => (a != 0).then(|| b % a),
+                        }
+                    };
+
+                    let mut alive = true;
+                    let mut write_a = |core: &mut Vec<Cell>, v: Option<usize>| match v {
+                        Some(v) => core[wpb].a = v,
+                        None => alive = false,
+                    };
+                    match ir.modifier {
+                        A => write_a(&mut self.core, op(ba, aa)),
+                        BA => write_a(&mut self.core, op(ba, ab)),
+                        F | I | Default => write_a(&mut self.core, op(ba, aa)),
+                        X => write_a(&mut self.core, op(ba, ab)),
+                        B | AB => (),
+                    }
+                    let mut write_b = |core: &mut Vec<Cell>, v: Option<usize>| match v {
+                        Some(v) => core[wpb].b = v,
+                        None => alive = false,
+                    };
+                    match ir.modifier {
+                        B => write_b(&mut self.core, op(bb, ab)),
+                        AB => write_b(&mut self.core, op(bb, aa)),
+                        F | I | Default => write_b(&mut self.core, op(bb, ab)),
+                        X => write_b(&mut self.core, op(bb, aa)),
+                        A | BA => (),
+                    }
+
+                    if alive {
+                        queue.push(next);
+                    }
+                }
+                OpCode::JMP => queue.push(rpa),
+                OpCode::JMZ | OpCode::JMN | OpCode::DJN => {
+                    let (test_a, test_b) = match ir.modifier {
+                        A | BA => (true, false),
+                        B | AB | Default => (false, true),
+                        F | X | I => (true, true),
+                    };
+
+                    let (mut va, mut vb) = (ba, bb);
+
+                    if let OpCode::DJN = ir.code {
+                        if test_a {
+                            self.core[wpb].a = (self.core[wpb].a + m - 1) % m;
+                            va = (va + m - 1) % m;
+                        }
+                        if test_b {
+                            self.core[wpb].b = (self.core[wpb].b + m - 1) % m;
+                            vb = (vb + m - 1) % m;
+                        }
+                    }
+
+                    let all_zero = (!test_a || va == 0) && (!test_b || vb == 0);
+
+                    let jump = match ir.code {
+                        OpCode::JMZ => all_zero,
+                        _ => !all_zero,
+                    };
+
+                    queue.push(if jump { rpa } else { next });
+                }
+                OpCode::CMP | OpCode::SEQ | OpCode::SNE => {
+                    let equal = match ir.modifier {
+                        A => aa == ba,
+                        B | Default => ab == bb,
+                        AB => aa == bb,
+                        BA => ab == ba,
+                        F => aa == ba && ab == bb,
+                        X => aa == bb && ab == ba,
+                        I => ira == irb,
+                    };
+
+                    let skip_next = match ir.code {
+                        OpCode::SNE => !equal,
+                        _ => equal,
+                    };
+
+                    queue.push(if skip_next { skip } else { next });
+                }
+                OpCode::SLT => {
+                    let lower = match ir.modifier {
+                        A => aa < ba,
+                        B | Default => ab < bb,
+                        AB => aa < bb,
+                        BA => ab < ba,
+                        F | I => aa < ba && ab < bb,
+                        X => aa < bb && ab < ba,
+                    };
+
+                    queue.push(if lower { skip } else { next });
+                }
+                OpCode::SPL => {
+                    queue.push(next);
+                    if self.warriors[0].1.len() + 1 < self.max_processes {
+                        queue.push(rpa);
+                    }
+                }
+            }
+
+            self.warriors[0].1.extend(queue);
+
+            if self.warriors[0].1.is_empty() {
+                self.warriors.remove(0);
+            } else {
+                self.warriors.rotate_left(1);
+            }
+        }
+    }
+}
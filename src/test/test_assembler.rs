@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod tests {
+    use crate::{assembler::evaluate_with, warrior::Warrior};
+
+    #[test]
+    fn test_assembler() {
+        let source = ";name Dwarf
+                    ORG start
+            step    EQU 4
+            start   ADD #step, bomb
+                    MOV bomb, @bomb
+                    JMP start
+            bomb    DAT #0, #0";
+        let dwarf = Warrior::parse(source.into(), "Dwarf".into(), 800).unwrap();
+        let expected = Warrior::load("src/test/warriors/dwarf.red", 800).unwrap();
+        assert_eq!((dwarf.org, dwarf.body), (expected.org, expected.body));
+
+        // EQUs are expanded where they're used, END can give the start too
+        let source = "gap EQU (top - here) * 2 + 1
+            here: DAT gap, -gap % 3
+            NOP.F $1, 0
+            top  JMP @here
+            END top";
+        let warrior = Warrior::parse(source.into(), "".into(), 800).unwrap();
+        assert_eq!(warrior.org, 2);
+        assert_eq!(warrior.body[0].to_string(), "DAT 5, -2");
+        assert_eq!(warrior.body[2].to_string(), "JMP @-2, 0");
+
+        let no_symbols = &mut |name: &str| Err(format!("unknown symbol {name}"));
+        assert_eq!(evaluate_with("-(7 + 2) / 2 * 3", no_symbols), Ok(-12));
+        assert!(evaluate_with("1 / 0", no_symbols).is_err());
+        assert!(evaluate_with("(1 + 2", no_symbols).is_err());
+        assert!(Warrior::parse("x EQU x\nDAT x".into(), "".into(), 800).is_err());
+        assert!(Warrior::parse("a DAT 0\na DAT 1".into(), "".into(), 800).is_err());
+        assert!(Warrior::parse("JMP nowhere".into(), "".into(), 800).is_err());
+    }
+}
@@ -0,0 +1,42 @@
+#[cfg(test)]
+mod tests {
+    use crate::{batch::Batch, battle::BattleConfig, warrior::Warrior};
+
+    #[test]
+    fn test_batch() {
+        let warriors = vec![
+            Warrior::load("src/test/warriors/dwarf.red", 800).unwrap(),
+            Warrior::load("src/test/warriors/imp.red", 800).unwrap(),
+            Warrior::load("src/test/warriors/spl_imp.red", 800).unwrap(),
+        ];
+        let matchups = vec![
+            vec![0, 1],
+            vec![1, 2],
+            vec![0, 2],
+            vec![2, 0],
+            vec![0, 1, 2],
+        ];
+
+        let mut config = BattleConfig::new(800);
+        config.rounds = 6;
+
+        let mut batch = Batch::new(config);
+        batch.seed = Some(42);
+
+        batch.threads = 1;
+        let serial = batch.run(&warriors, &matchups).unwrap();
+        assert_eq!(serial.len(), matchups.len());
+        for (matchup, result) in matchups.iter().zip(serial.iter()) {
+            for slot in 0..matchup.len() {
+                let rounds = result.wins[slot] + result.losses[slot] + result.ties[slot];
+                assert_eq!(rounds, 6);
+            }
+        }
+
+        batch.threads = 4;
+        assert_eq!(batch.run(&warriors, &matchups).unwrap(), serial);
+        assert_eq!(batch.run(&warriors, &matchups).unwrap(), serial);
+
+        assert!(batch.run(&warriors, &[vec![0, 3]]).is_err());
+    }
+}
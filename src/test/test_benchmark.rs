@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        batch::Batch,
+        battle::BattleConfig,
+        benchmark::Benchmark,
+        evolution::{Evolution, EvolutionConfig},
+        warrior::Warrior,
+    };
+
+    #[test]
+    fn test_benchmark() {
+        let mut battle = BattleConfig::new(800);
+        battle.max_cycles = 800;
+        let dwarf = Warrior::load("src/test/warriors/dwarf.red", 800).unwrap();
+        let suicide = Warrior::load("src/test/warriors/suicide.red", 800).unwrap();
+
+        let set = Benchmark::load_dir("src/test/warriors", 800).unwrap();
+        let names: Vec<&str> = set.warriors.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, ["Dwarf", "Imp", "Spl imp", "Suicide"]);
+        assert!(Benchmark::load_dir("src/test", 800).is_err());
+
+        let mut batch = Batch::new(battle.clone());
+        batch.seed = Some(1);
+
+        // winning every round is 300, losing every round 0
+        let mut benchmark = Benchmark::new(vec![suicide.clone(), suicide.clone()]);
+        benchmark.rounds = 5;
+        let scores = benchmark
+            .score(std::slice::from_ref(&dwarf), &batch)
+            .unwrap();
+        assert_eq!(scores, [300.]);
+        let benchmark = Benchmark::new(vec![dwarf.clone()]);
+        let scores = benchmark
+            .score(std::slice::from_ref(&suicide), &batch)
+            .unwrap();
+        assert_eq!(scores, [0.]);
+
+        // the dwarf beats the suicides and they lose to everyone
+        let mut config = EvolutionConfig::new(battle);
+        config.seed = Some(3);
+        config.benchmark = Some(Benchmark::new(vec![suicide.clone()]));
+        let population = vec![suicide.clone(), dwarf, suicide];
+        let mut evolution = Evolution::with_population(config, population.clone()).unwrap();
+        evolution.evaluate().unwrap();
+        assert_eq!(evolution.scores, [0., 300., 0.]);
+
+        // the round robin adds 300 per win against the rest
+        evolution.config.pool_weight = 0.5;
+        evolution.population = population;
+        evolution.evaluate().unwrap();
+        assert_eq!(evolution.scores[1], 300. + 0.5 * 300.);
+    }
+}
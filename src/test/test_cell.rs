@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod tests {
+    use std::mem::size_of;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        core::CoreConfig,
+        instruction::{
+            cell::{Cell, Mode},
+            instruction::Instruction,
+            op_code::OpCode,
+            op_modifier::OpModifier,
+        },
+        utils::ModUsize,
+        warrior::Warrior,
+    };
+
+    #[test]
+    fn test_cell() {
+        assert_eq!(size_of::<Cell>(), 12);
+
+        for core_size in [1, 80, 8000, 55440] {
+            for _ in 0..1000 {
+                let mut instruction = Instruction::get_random(core_size, core_size);
+                if instruction.fields[0].get_val().val.is_multiple_of(3) {
+                    instruction.modifier = OpModifier::Default;
+                }
+
+                let cell = Cell::from_instruction(&instruction, core_size);
+                assert_eq!(cell.to_instruction(core_size), instruction);
+                assert_eq!(cell.get_modifier(), instruction.get_modifier());
+            }
+        }
+
+        // setters decode the handler again, a default modifier follows the modes
+        let mut cell = Cell::EMPTY;
+        cell.set_code(OpCode::MOV);
+        cell.set_modes([Mode::Inmediate, Mode::Direct]);
+        assert_eq!(cell.get_modifier(), OpModifier::AB);
+        let modes = [Mode::Inmediate, Mode::Direct];
+        assert_eq!(
+            cell,
+            Cell::new(OpCode::MOV, OpModifier::Default, modes, [0, 0])
+        );
+        cell.set_modifier(OpModifier::I);
+        cell.set_value(1, 7);
+        assert_eq!(cell, Cell::new(OpCode::MOV, OpModifier::I, modes, [0, 7]));
+    }
+
+    #[test]
+    fn test_cell_other_core_size() {
+        // parsed for 8000 cells, the dwarf's JMP -2 is JMP 7998
+        let dwarf = Warrior::load("src/test/warriors/dwarf.red", 8000).unwrap();
+        let cell = Cell::from_instruction(&dwarf.body[2], 80);
+        assert!(cell.values().iter().all(|value| *value < 80));
+
+        let mut config = CoreConfig::new(80);
+        config
+            .deploy(dwarf.clone(), Some(ModUsize::new(0, 80)))
+            .unwrap();
+        let mut runtime = config.brawl();
+        for _ in 0..1000 {
+            runtime.tick();
+        }
+        assert!(runtime.core.iter().flat_map(Cell::values).all(|v| v < 80));
+
+        let mut rng = StdRng::seed_from_u64(1);
+        runtime.load_with_rng(&[&dwarf], 80, 1, &mut rng).unwrap();
+        for _ in 0..1000 {
+            runtime.tick();
+        }
+        assert!(runtime.core.iter().flat_map(Cell::values).all(|v| v < 80));
+    }
+}
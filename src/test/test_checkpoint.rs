@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use crate::{
+        battle::BattleConfig,
+        evolution::{Evolution, EvolutionConfig, GenerationReport},
+        hall_of_fame::{HallOfFameConfig, Sampling},
+        warrior::Warrior,
+    };
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("core_war_vm_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn config(dir: &Path) -> EvolutionConfig {
+        let mut battle = BattleConfig::new(800);
+        battle.max_cycles = 800;
+        battle.rounds = 2;
+
+        let mut config = EvolutionConfig::new(battle);
+        config.population = 6;
+        config.generations = 4;
+        config.warrior_length = 5;
+        config.seed = Some(11);
+        config.checkpoint = Some(dir.to_path_buf());
+        config.statistics = Some(dir.with_extension("csv"));
+        config.lineage = Some(dir.with_extension("lineage.csv"));
+        // sampled at random, so the archive and the RNG both have to be restored
+        config.hall_of_fame = Some(HallOfFameConfig {
+            size: 2,
+            sampling: Sampling::Random(1),
+            weight: 1.,
+        });
+        config
+    }
+
+    /// Load files don't keep how far random instructions were made to
+    /// point, which evolution doesn't look at, so that is left out.
+    fn as_loaded(warrior: &Warrior) -> Warrior {
+        let mut warrior = warrior.clone();
+        for instruction in warrior.body.iter_mut() {
+            instruction.ptr_range = instruction.core_size;
+        }
+        warrior
+    }
+
+    fn reports_as_loaded(reports: &[GenerationReport]) -> Vec<GenerationReport> {
+        let mut reports = reports.to_vec();
+        for report in reports.iter_mut() {
+            report.best = as_loaded(&report.best);
+        }
+        reports
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        // a load file keeps the body and the start
+        let mut dwarf = Warrior::load("src/test/warriors/dwarf.red", 800).unwrap();
+        dwarf.org = 2;
+        let source = dwarf.to_load_file();
+        assert_eq!(
+            Warrior::parse(source, dwarf.name.clone(), 800).unwrap(),
+            dwarf
+        );
+
+        let dir = scratch_dir("checkpoint");
+        let mut evolution = Evolution::new(config(&dir)).unwrap();
+        let uninterrupted = evolution.run().unwrap();
+
+        let logs = [dir.with_extension("csv"), dir.with_extension("lineage.csv")];
+        let read_logs = || logs.clone().map(|path| fs::read_to_string(path).unwrap());
+        let written = read_logs();
+        assert_eq!(written[0].lines().count(), 1 + 4);
+
+        let saved = fs::read_to_string(dir.join("generation_000003/0.red")).unwrap();
+        assert!(saved.starts_with(";generation 3\n;score "));
+
+        // pretend the run stopped after evaluating generation 1
+        for generation in 2..4 {
+            fs::remove_dir_all(dir.join(format!("generation_{generation:06}"))).unwrap();
+        }
+        fs::create_dir(dir.join("generation_000009")).unwrap();
+
+        let mut resumed = Evolution::resume(config(&dir), &dir).unwrap();
+        assert_eq!(resumed.generation, 1);
+        assert_eq!(resumed.hall_of_fame.as_ref().unwrap().champions.len(), 1);
+        assert_eq!(
+            reports_as_loaded(&resumed.run().unwrap()),
+            reports_as_loaded(&uninterrupted[1..])
+        );
+        let population: Vec<Warrior> = evolution.population.iter().map(as_loaded).collect();
+        let resumed: Vec<Warrior> = resumed.population.iter().map(as_loaded).collect();
+        assert_eq!(resumed, population);
+        // rows the interrupted run had written past generation 1 aren't repeated
+        assert_eq!(read_logs(), written);
+
+        // a new run starts the logs over
+        Evolution::new(config(&dir)).unwrap();
+        assert_eq!(read_logs(), ["", ""]);
+        for path in logs {
+            fs::remove_file(path).unwrap();
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(Evolution::resume(config(&dir), &dir).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod tests {
+    use crate::cli::{self, CliError};
+
+    fn run(args: &str, input: &str) -> Result<String, CliError> {
+        let args: Vec<String> = args.split_whitespace().map(String::from).collect();
+        let mut out = vec![];
+        cli::main(&args, &mut input.as_bytes(), &mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    const RULES: &str = "-s 800 -c 8000 -l 10 -d 10 --seed 1";
+
+    #[test]
+    fn test_cli() {
+        let w = "src/test/warriors";
+
+        // the dwarf always kills the suicide
+        let text = run(
+            &format!("battle {w}/dwarf.red {w}/suicide.red -r 5 {RULES}"),
+            "",
+        )
+        .unwrap();
+        assert_eq!(text, "Dwarf scores 15\nSuicide scores 0\nResults: 5 0 0\n");
+        let json = run(
+            &format!("battle {w}/dwarf.red {w}/suicide.red -r 5 {RULES} --json"),
+            "",
+        );
+        assert!(json
+            .unwrap()
+            .starts_with("{\"rounds\":5,\"warriors\":[{\"name\":\"Dwarf\",\"wins\":5,"));
+
+        // an environment sets the rules the flags then change
+        let text = run(
+            &format!("battle {w}/dwarf.red {w}/suicide.red --env tiny -r 2 --seed 1"),
+            "",
+        );
+        assert_eq!(
+            text.unwrap(),
+            "Dwarf scores 6\nSuicide scores 0\nResults: 2 0 0\n"
+        );
+        assert_eq!(
+            run("battle a.red --env 94y", "").unwrap_err().exit_code(),
+            2
+        );
+        assert_eq!(run("asm a.red --env 94y", "").unwrap_err().exit_code(), 2);
+        assert_eq!(
+            run("battle a.red --env missing.toml", "")
+                .unwrap_err()
+                .exit_code(),
+            1
+        );
+
+        let load_file = run(&format!("asm {w}/imp.red"), "").unwrap();
+        assert_eq!(load_file, ";redcode-94\n;name Imp\nORG 0\nMOV 0, 1\nEND\n");
+
+        let csv = run(&format!("tournament {w} -r 4 {RULES} --format csv"), "").unwrap();
+        assert_eq!(csv.lines().count(), 1 + 4 + 1 + 1 + 4);
+
+        // a scripted debugging session runs to the end
+        let session = run(
+            &format!("debug {w}/dwarf.red {w}/suicide.red {RULES}"),
+            "s 2\np\nc\n",
+        );
+        let session = session.unwrap();
+        assert!(session.contains("Dwarf starts at"));
+        assert!(session.trim_end().ends_with("over, alive: Dwarf"));
+
+        // usage errors exit with 2, everything else with 1
+        assert_eq!(run("", "").unwrap_err().exit_code(), 2);
+        assert_eq!(run("fly", "").unwrap_err().exit_code(), 2);
+        assert_eq!(run("battle -r", "").unwrap_err().exit_code(), 2);
+        assert_eq!(run("battle a.red -x 1", "").unwrap_err().exit_code(), 2);
+        assert_eq!(run("battle -r ten", "").unwrap_err().exit_code(), 2);
+        assert_eq!(run("asm missing.red", "").unwrap_err().exit_code(), 1);
+
+        // evolution configs
+        let islands = cli::islands_config(
+            r#"
+            population = 6
+            generations = 2
+            selection = "roulette"
+            [battle]
+            core_size = 800
+            [mutation]
+            use_constant = 2
+            constants = [3, 7]
+            [islands]
+            count = 3
+            topology = "fully_connected"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(islands.islands.len(), 3);
+        let config = &islands.islands[0];
+        assert_eq!((config.population, config.battle.core_size), (6, 800));
+        assert_eq!(config.mutation.use_constant, 2.);
+
+        assert_eq!(config.mutation.constants, [3, 7]);
+
+        let islands = cli::islands_config("environment = \"nano\"").unwrap();
+        assert_eq!(islands.islands[0].battle.core_size, 80);
+        assert!(cli::islands_config("[environment]\nstandard = \"88\"").is_err());
+
+        assert_eq!(
+            cli::islands_config("populaton = 6").unwrap_err(),
+            "unknown key populaton"
+        );
+        let typo = cli::islands_config("[mutation]\nswop = 1").unwrap_err();
+        assert_eq!(typo, "unknown key mutation.swop");
+        // a table given twice, and a quoted key that only looks dotted
+        assert!(cli::islands_config("[battle]\nrounds = 1\n[battle]\nrounds = 2").is_err());
+        assert!(cli::islands_config("\"battle.rounds\" = 1").is_err());
+    }
+}
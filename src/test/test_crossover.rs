@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        battle::BattleConfig,
+        crossover::{CrossoverConfig, CrossoverOperator},
+        warrior::Warrior,
+    };
+
+    /// Whether every instruction of `offspring` sits at the same offset from
+    /// its org as in one of the parents.
+    fn aligned(offspring: &Warrior, a: &Warrior, b: &Warrior) -> bool {
+        (0..offspring.body.len()).all(|i| {
+            let offset = i as isize - offspring.org as isize;
+            [a, b].iter().any(|parent| {
+                let index = offset + parent.org as isize;
+                index >= 0 && parent.body.get(index as usize) == Some(&offspring.body[i])
+            })
+        })
+    }
+
+    #[test]
+    fn test_crossover() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let config = CrossoverConfig::new(&BattleConfig::new(800));
+
+        // a bomber behind a two instruction decoy, and a shorter imp spawner
+        let mut bomber = Warrior::load("src/test/warriors/dwarf.red", 800).unwrap();
+        bomber.org = 2;
+        let spl_imp = Warrior::load("src/test/warriors/spl_imp.red", 800).unwrap();
+
+        for operator in CrossoverOperator::ALL {
+            for _ in 0..50 {
+                let offspring = config.apply(&bomber, &spl_imp, operator, &mut rng);
+                assert!(offspring.org < offspring.body.len());
+                assert!(aligned(&offspring, &bomber, &spl_imp));
+                // the org is always taken from one parent or the other
+                let start = offspring.body[offspring.org];
+                assert!(start == bomber.body[2] || start == spl_imp.body[0]);
+            }
+        }
+
+        // without alignment a single cut keeps the positions of both parents
+        let unaligned = CrossoverConfig {
+            align_org: false,
+            ..config.clone()
+        };
+        for _ in 0..50 {
+            let offspring =
+                unaligned.apply(&bomber, &spl_imp, CrossoverOperator::OnePoint, &mut rng);
+            let cut = (0..offspring.body.len())
+                .find(|i| bomber.body[*i] != offspring.body[*i])
+                .unwrap_or(offspring.body.len());
+            assert!(offspring.body[cut..]
+                .iter()
+                .zip(&spl_imp.body[cut.min(2)..])
+                .all(|(a, b)| a == b));
+        }
+
+        // offspring are cut down to the length limit
+        let capped = CrossoverConfig {
+            max_length: 2,
+            ..config.clone()
+        };
+        for _ in 0..50 {
+            let (offspring, _) = capped.crossover(&bomber, &spl_imp, &mut rng).unwrap();
+            assert!(offspring.body.len() <= 2);
+            assert!(offspring.org < offspring.body.len());
+        }
+
+        let only_uniform = CrossoverConfig {
+            one_point: 0.,
+            two_point: 0.,
+            ..config.clone()
+        };
+        let (_, operator) = only_uniform.crossover(&bomber, &spl_imp, &mut rng).unwrap();
+        assert_eq!(operator, CrossoverOperator::Uniform);
+
+        let disabled = CrossoverConfig {
+            uniform: 0.,
+            ..only_uniform
+        };
+        assert!(disabled.crossover(&bomber, &spl_imp, &mut rng).is_none());
+    }
+}
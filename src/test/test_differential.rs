@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::test::differential::harness::{diff_snapshot, record, run_differential, Trace};
+
+    /// Every trace checked in, the battle it plays and how many ticks long
+    /// it is. All of them are recorded from the reference model.
+    pub const TRACES: [(&str, &str, usize); 6] = [
+        (
+            "src/test/traces/dwarf.trace",
+            "CORESIZE 8000\nWARRIOR src/test/warriors/dwarf.red 0\n",
+            400,
+        ),
+        (
+            "src/test/traces/dwarf_vs_imp.trace",
+            "CORESIZE 8000\nSEED 7\nWARRIOR src/test/warriors/dwarf.red\nWARRIOR src/test/warriors/imp.red\n",
+            600,
+        ),
+        (
+            "src/test/traces/spl_imp.trace",
+            "CORESIZE 8000\nMAXPROCESSES 16\nWARRIOR src/test/warriors/spl_imp.red 0\n",
+            300,
+        ),
+        (
+            "src/test/traces/paper_vs_vampire.trace",
+            "CORESIZE 8000\nMAXPROCESSES 64\nSEED 3\nWARRIOR src/test/warriors/split/paper.red\nWARRIOR src/test/warriors/split/vampire.red\n",
+            1500,
+        ),
+        (
+            "src/test/traces/silk_vs_dwarf.trace",
+            "CORESIZE 8000\nMAXPROCESSES 64\nSEED 11\nWARRIOR src/test/warriors/split/silk.red\nWARRIOR src/test/warriors/dwarf.red\n",
+            1500,
+        ),
+        (
+            "src/test/traces/vampire_vs_dwarf.trace",
+            "CORESIZE 79\nMAXPROCESSES 32\nWARRIOR src/test/warriors/split/vampire.red 0\nWARRIOR src/test/warriors/dwarf.red 40\n",
+            1500,
+        ),
+    ];
+
+    #[test]
+    fn test_differential() {
+        for (trace, _, _) in TRACES {
+            if let Err(report) = run_differential(trace) {
+                panic!("{report}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_differential_reports_divergence() {
+        let trace = Trace::load("src/test/traces/dwarf.trace").unwrap();
+        assert_eq!(trace.snapshots.len(), 401);
+
+        // the runtime one tick ahead of cycle 1, with the bomb already dropped
+        let mut runtime = trace.core_config().unwrap().brawl();
+        runtime.tick();
+        runtime.tick();
+
+        let expected = trace.core_at(1);
+        let report = diff_snapshot(&runtime, &expected, &trace.snapshots[1].queues).unwrap();
+        assert!(report.contains("0007 - DAT.F 0, 0"));
+        assert!(report.contains("+ DAT.F #0, #4"));
+        assert!(report.contains("queues + [(0, [2])]"));
+
+        // traces have to go tick by tick
+        assert!(Trace::parse("SOURCE x\nCORESIZE 10\nCYCLE 0\nCYCLE 2").is_err());
+    }
+
+    /// Rewrites every trace with the reference model, for when the battles
+    /// above change. `cargo test record_traces -- --ignored`
+    #[test]
+    #[ignore]
+    fn record_traces() {
+        for (path, header, cycles) in TRACES {
+            fs::write(path, record(header, cycles).unwrap()).unwrap();
+        }
+    }
+}
@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{
+        environment::{Environment, PRESETS},
+        settings,
+    };
+
+    #[test]
+    fn test_environment() {
+        for name in PRESETS {
+            let environment = Environment::preset(name).unwrap();
+            assert_eq!(environment.validate(), Ok(()));
+        }
+        let nano = Environment::preset("nano").unwrap();
+        assert_eq!(
+            (
+                nano.battle.core_size,
+                nano.battle.max_length,
+                nano.pspace_size
+            ),
+            (80, 5, 5)
+        );
+
+        // limits and p-space follow a new core size unless they're given
+        let table = settings::parse("preset = \"tiny\"\ncore_size = 1600\nrounds = 10").unwrap();
+        let environment = Environment::from_table(&table).unwrap();
+        assert_eq!(environment.battle.core_size, 1600);
+        assert_eq!(environment.battle.max_cycles, 8000);
+        assert_eq!(environment.battle.rounds, 10);
+        assert_eq!(
+            (environment.read_limit, environment.pspace_size),
+            (1600, 100)
+        );
+
+        let path = std::env::temp_dir().join(format!(
+            "core_war_vm_environment_{}.toml",
+            std::process::id()
+        ));
+        fs::write(&path, "preset = \"lp\"\npspace_size = 16\n").unwrap();
+        let path = path.to_str().unwrap();
+        let environment = Environment::find(path).unwrap();
+        assert_eq!(environment.name, path);
+        assert_eq!(
+            (environment.battle.max_processes, environment.pspace_size),
+            (8, 16)
+        );
+        fs::remove_file(path).unwrap();
+
+        for source in [
+            "preset = \"huge\"",
+            "cores = 8000",
+            "standard = \"88\"",
+            "read_limit = 400",
+            "core_size = 0",
+            "core_size = -800",
+            "preset = \"tiny\"\npreset = \"nano\"",
+        ] {
+            let environment = settings::parse(source).and_then(|t| Environment::from_table(&t));
+            assert!(environment.is_err(), "{source}");
+        }
+        assert!(Environment::find("94y").is_err());
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        battle::BattleConfig,
+        evolution::{Evolution, EvolutionConfig, Selection},
+        warrior::Warrior,
+    };
+
+    fn small_config() -> EvolutionConfig {
+        let mut battle = BattleConfig::new(800);
+        battle.max_cycles = 800;
+        battle.rounds = 2;
+
+        let mut config = EvolutionConfig::new(battle);
+        config.population = 6;
+        config.generations = 3;
+        config.warrior_length = 5;
+        config.seed = Some(7);
+        config
+    }
+
+    #[test]
+    fn test_selection() {
+        let mut evolution = Evolution::new(small_config()).unwrap();
+        evolution.scores = vec![1., 9., 3., 0., 9., 5.];
+
+        // best first, so the old main.rs mistake of breeding from the worst is gone
+        let ranking = evolution.ranking();
+        assert_eq!(ranking, vec![1, 4, 5, 2, 0, 3]);
+
+        evolution.config.selection = Selection::Truncation(0.5);
+        for _ in 0..100 {
+            assert!([1, 4, 5].contains(&evolution.select(&ranking)));
+        }
+
+        // a tournament as large as the population is won by a best warrior
+        evolution.config.selection = Selection::Tournament(1000);
+        for _ in 0..10 {
+            assert!([1, 4].contains(&evolution.select(&ranking)));
+        }
+
+        evolution.scores = vec![0., 0., 0., 0., 1., 0.];
+        evolution.config.selection = Selection::Roulette;
+        for _ in 0..100 {
+            assert_eq!(evolution.select(&ranking), 4);
+        }
+    }
+
+    #[test]
+    fn test_evolution() {
+        let dwarf = Warrior::load("src/test/warriors/dwarf.red", 800).unwrap();
+        let suicide = Warrior::load("src/test/warriors/suicide.red", 800).unwrap();
+
+        let mut config = small_config();
+        config.elitism = 1;
+        config.mutation_rate = 1.;
+        let mut evolution = Evolution::with_population(
+            config,
+            vec![suicide.clone(), dwarf.clone(), suicide.clone(), suicide],
+        )
+        .unwrap();
+
+        let report = evolution.step().unwrap();
+        assert_eq!(report.generation, 0);
+        assert_eq!(report.best, dwarf);
+        // 3 points a round against every other warrior, times 100
+        assert_eq!(report.best_score, 300.);
+        // the elite survives unmutated, everything else was mutated
+        assert_eq!(evolution.population[0], dwarf);
+        assert_eq!(evolution.population.len(), 4);
+        assert_eq!(evolution.generation, 1);
+
+        // a seed fixes the whole run
+        let first = Evolution::new(small_config()).unwrap().run().unwrap();
+        let second = Evolution::new(small_config()).unwrap().run().unwrap();
+        assert_eq!(first.len(), 3);
+        assert_eq!(first, second);
+
+        // nothing to build a population of, or to mutate
+        let mut config = small_config();
+        config.warrior_length = 0;
+        assert!(Evolution::new(config).is_err());
+        let mut empty = dwarf.clone();
+        empty.body.clear();
+        assert_eq!(empty.mutate().body, vec![]);
+    }
+}
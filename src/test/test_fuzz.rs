@@ -0,0 +1,160 @@
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::{
+        core::{CoreConfig, CoreRuntime},
+        instruction::{
+            cell::Cell as RuntimeCell, instruction::Instruction, op_modifier::OpModifier,
+        },
+        test::reference::model::{Cell, ReferenceMars},
+        utils::ModUsize,
+        warrior::Warrior,
+    };
+
+    const CASES: usize = 3000;
+    const TICKS: usize = 40;
+    const FIXED_SEED: u64 = 94;
+
+    fn random_runtime(rng: &mut impl Rng) -> CoreRuntime {
+        let core_size = if rng.gen_bool(0.01) {
+            8000
+        } else {
+            rng.gen_range(1..=40)
+        };
+
+        let core = (0..core_size)
+            .map(|_| {
+                let mut instruction = Instruction::get_random_with_rng(core_size, core_size, rng);
+                if rng.gen_bool(0.3) {
+                    instruction.modifier = OpModifier::Default;
+                }
+                RuntimeCell::from_instruction(&instruction, core_size)
+            })
+            .collect();
+
+        let max_processes = rng.gen_range(1..=8);
+
+        let warriors = (0..rng.gen_range(1..=3))
+            .map(|i| {
+                let mut warrior = Warrior::parse("".into(), format!("w{i}"), core_size).unwrap();
+                // an empty process queue is a legal, if dead, warrior
+                for _ in 0..rng.gen_range(0..=max_processes) {
+                    warrior.new_thread(ModUsize::rand(core_size, 0..core_size, rng));
+                }
+                warrior
+            })
+            .collect();
+
+        let mut runtime = CoreConfig::new(core_size).brawl();
+        runtime.max_processes = max_processes;
+        runtime.core = core;
+        runtime.warriors = warriors;
+        runtime
+    }
+
+    fn check_invariants(runtime: &CoreRuntime) -> Result<(), String> {
+        let valid = |x: &ModUsize| x.congruence == runtime.core_size && x.val < x.congruence;
+
+        for (i, cell) in runtime.core.iter().enumerate() {
+            if cell
+                .values()
+                .iter()
+                .any(|v| *v as usize >= runtime.core_size)
+            {
+                return Err(format!("cell {i} holds an unwrapped value: {cell:?}"));
+            }
+        }
+
+        for warrior in runtime.warriors.iter() {
+            if warrior.thread_count() > runtime.max_processes {
+                return Err(format!("{} exceeds the process limit", warrior.name));
+            }
+            if !warrior.get_counters().iter().all(valid) {
+                return Err(format!("{} has an unwrapped counter", warrior.name));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn compare(runtime: &CoreRuntime, reference: &ReferenceMars) -> Result<(), String> {
+        for (i, (actual, expected)) in runtime.core.iter().zip(reference.core.iter()).enumerate() {
+            let actual = actual.to_instruction(runtime.core_size);
+            if Cell::from_instruction(&actual) != *expected {
+                return Err(format!(
+                    "cell {i}: runtime {actual} != reference {expected:?}"
+                ));
+            }
+        }
+
+        let actual: Vec<(String, Vec<usize>)> = runtime
+            .warriors
+            .iter()
+            .filter(|w| !w.dead())
+            .map(|w| {
+                let queue = w.get_counters().iter().map(|ic| ic.val).collect();
+                (w.name.clone(), queue)
+            })
+            .collect();
+        let expected: Vec<(String, Vec<usize>)> = reference
+            .warriors
+            .iter()
+            .filter(|(_, queue)| !queue.is_empty())
+            .map(|(name, queue)| (name.clone(), queue.iter().copied().collect()))
+            .collect();
+
+        if actual != expected {
+            return Err(format!(
+                "process queues: runtime {actual:?} != reference {expected:?}"
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fuzz() {
+        // the same cases every run, FUZZ_SEED=random for new ones and
+        // FUZZ_SEED=<n> to replay a failure, the seed is in its message
+        let seed = match std::env::var("FUZZ_SEED").as_deref() {
+            Err(_) => FIXED_SEED,
+            Ok("random") => rand::random(),
+            Ok(seed) => seed
+                .parse()
+                .expect("FUZZ_SEED should be a number or random"),
+        };
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        for case in 0..CASES {
+            let mut runtime = random_runtime(&mut rng);
+            let mut reference = ReferenceMars::from_runtime(&runtime);
+
+            for tick in 0..TICKS {
+                let before = runtime.core.clone();
+                let executing = runtime
+                    .warriors
+                    .iter()
+                    .find(|w| !w.dead())
+                    .and_then(|w| w.get_counters().first().copied())
+                    .map(|ic| runtime.get_instruction_at(&ic).to_string());
+
+                runtime.tick();
+                reference.step();
+
+                if let Err(err) =
+                    check_invariants(&runtime).and_then(|_| compare(&runtime, &reference))
+                {
+                    let before: Vec<String> = before
+                        .iter()
+                        .map(|c| c.to_instruction(runtime.core_size).to_string())
+                        .collect();
+                    panic!(
+                        "FUZZ_SEED={seed}, case {case}, tick {tick} executing {executing:?} on core of {}: {err}\ncore before the tick: {before:?}",
+                        runtime.core_size
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        batch::Batch,
+        battle::BattleConfig,
+        evolution::{Evolution, EvolutionConfig},
+        hall_of_fame::{HallOfFame, HallOfFameConfig, Sampling},
+        warrior::Warrior,
+    };
+
+    #[test]
+    fn test_hall_of_fame() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let mut battle = BattleConfig::new(800);
+        battle.max_cycles = 800;
+        battle.rounds = 2;
+        let mut batch = Batch::new(battle.clone());
+        batch.seed = Some(1);
+
+        let dwarf = Warrior::load("src/test/warriors/dwarf.red", 800).unwrap();
+        let suicide = Warrior::load("src/test/warriors/suicide.red", 800).unwrap();
+
+        // only the latest champions are kept
+        let mut hall_of_fame = HallOfFame::new(HallOfFameConfig {
+            size: 3,
+            sampling: Sampling::All,
+            weight: 0.5,
+        });
+        for generation in 0..5 {
+            let champion = if generation % 2 == 0 {
+                &suicide
+            } else {
+                &dwarf
+            };
+            hall_of_fame.add(generation, champion.clone());
+        }
+        let generations: Vec<usize> = hall_of_fame
+            .champions
+            .iter()
+            .map(|c| c.generation)
+            .collect();
+        assert_eq!(generations, [2, 3, 4]);
+
+        assert_eq!(
+            hall_of_fame.sample(&mut rng),
+            [suicide.clone(), dwarf.clone(), suicide.clone()]
+        );
+        hall_of_fame.config.sampling = Sampling::Latest(1);
+        assert_eq!(
+            hall_of_fame.sample(&mut rng),
+            std::slice::from_ref(&suicide)
+        );
+        hall_of_fame.config.sampling = Sampling::Random(2);
+        assert_eq!(hall_of_fame.sample(&mut rng).len(), 2);
+
+        // beating every sampled champion is worth the weight times 300
+        hall_of_fame.config.sampling = Sampling::Latest(1);
+        let scores = hall_of_fame.score(&[dwarf.clone(), suicide.clone()], &batch, &mut rng);
+        assert_eq!(scores.unwrap()[0], 150.);
+
+        // the dwarf beats the older suicide, the newer suicide loses to it
+        let ciao = hall_of_fame.ciao(&batch).unwrap();
+        assert_eq!(ciao.generations, [2, 3, 4]);
+        assert_eq!(ciao.scores[1], [300.]);
+        assert_eq!(ciao.scores[2][1], 0.);
+        assert!(ciao.to_string().starts_with("     2 \n     3 @\n     4 "));
+
+        // evolution adds its champion of every generation
+        let mut config = EvolutionConfig::new(battle);
+        config.hall_of_fame = Some(HallOfFameConfig::new());
+        config.seed = Some(4);
+        let mut evolution =
+            Evolution::with_population(config, vec![suicide.clone(), dwarf.clone(), suicide])
+                .unwrap();
+        evolution.step().unwrap();
+        evolution.step().unwrap();
+
+        let hall_of_fame = evolution.hall_of_fame.unwrap();
+        assert_eq!(hall_of_fame.champions.len(), 2);
+        assert_eq!(hall_of_fame.champions[0].warrior, dwarf);
+        assert_eq!(hall_of_fame.champions[1].generation, 1);
+    }
+}
@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        io::{Read, Write},
+        net::{Shutdown, TcpListener, TcpStream},
+        path::PathBuf,
+        thread,
+    };
+
+    use crate::{
+        battle::BattleConfig,
+        hill::{read_submission, Hill, MAX_SUBMISSION},
+    };
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("core_war_vm_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn config() -> BattleConfig {
+        let mut config = BattleConfig::new(800);
+        config.rounds = 2;
+        config
+    }
+
+    #[test]
+    fn test_hill() {
+        let dir = scratch_dir("hill");
+        let mut hill = Hill::create(&dir, "test", config(), 3).unwrap();
+
+        for file in ["suicide", "dwarf", "imp"] {
+            let source = fs::read_to_string(format!("src/test/warriors/{file}.red")).unwrap();
+            let report = hill.challenge(&source).unwrap();
+            assert!(report.rank.is_some());
+            assert_eq!(report.pushed_off, None);
+        }
+
+        let source = fs::read_to_string("src/test/warriors/spl_imp.red").unwrap();
+        let report = hill.challenge(&source).unwrap();
+        assert_eq!(report.pushed_off.as_deref(), Some("Suicide"));
+        assert_eq!(report.standings.len(), 3);
+
+        // a second suicide can't beat anyone and never enters the hill
+        let report = hill.challenge(";name Suicide 2\nDAT 0, 0\n").unwrap();
+        assert_eq!(report.rank, None);
+        assert!(hill.challenge("MOV 0, 1\n".repeat(11).as_str()).is_err());
+
+        let reopened = Hill::open(&dir).unwrap();
+        assert_eq!(reopened.config, hill.config);
+        assert_eq!(reopened.scores, hill.scores);
+        let names = |h: &Hill| -> Vec<(String, usize)> {
+            h.members
+                .iter()
+                .map(|m| (m.warrior.name.clone(), m.age))
+                .collect()
+        };
+        assert_eq!(names(&reopened), names(&hill));
+
+        let history = fs::read_to_string(dir.join("history.log")).unwrap();
+        assert_eq!(history.lines().count(), 5);
+
+        // submissions can also be dropped in a spool directory
+        let spool = dir.join("spool");
+        fs::create_dir_all(&spool).unwrap();
+        fs::copy("src/test/warriors/dwarf.red", spool.join("dwarf.red")).unwrap();
+
+        let mut hill = reopened;
+        let reports = hill.process_spool(&spool).unwrap();
+        assert_eq!(reports.len(), 1);
+        assert!(!spool.join("dwarf.red").exists());
+        assert!(spool.join("done/dwarf.txt").exists());
+
+        // one that can't be read is put aside, and the rest still played
+        fs::create_dir_all(spool.join("broken.red")).unwrap();
+        fs::copy("src/test/warriors/imp.red", spool.join("imp.red")).unwrap();
+        let reports = hill.process_spool(&spool).unwrap();
+        assert_eq!(reports.len(), 1);
+        assert!(spool.join("failed/broken.red").is_dir());
+        assert!(spool.join("done/imp.txt").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_hill_tcp() {
+        let dir = scratch_dir("hill_tcp");
+        let mut hill = Hill::create(&dir, "test", config(), 3).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream.write_all(b";name Imp\nMOV 0, 1\n").unwrap();
+            stream.shutdown(Shutdown::Write).unwrap();
+
+            let mut answer = String::new();
+            stream.read_to_string(&mut answer).unwrap();
+            answer
+        });
+
+        let (mut stream, _) = listener.accept().unwrap();
+        hill.handle_connection(&mut stream).unwrap();
+        drop(stream);
+
+        let answer = client.join().unwrap();
+        assert!(answer.starts_with("Imp entered the hill at rank 1"));
+
+        // oversized submissions are cut off rather than read to the end
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            let _ = stream.write_all(&vec![b';'; MAX_SUBMISSION as usize + 10]);
+        });
+        let (stream, _) = listener.accept().unwrap();
+        let err = read_submission(&stream).unwrap_err();
+        assert!(err.contains("over"), "{err}");
+        drop(stream);
+        client.join().unwrap();
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
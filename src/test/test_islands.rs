@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        battle::BattleConfig,
+        evolution::EvolutionConfig,
+        islands::{Islands, IslandsConfig, Topology, ISLAND_ID_BITS},
+        warrior::Warrior,
+    };
+
+    fn small_config() -> IslandsConfig {
+        let mut battle = BattleConfig::new(800);
+        battle.max_cycles = 800;
+        battle.rounds = 2;
+
+        let mut config = EvolutionConfig::new(battle);
+        config.population = 4;
+        config.generations = 5;
+        config.warrior_length = 5;
+        config.elitism = 1;
+        config.seed = Some(5);
+
+        let mut config = IslandsConfig::new(config, 3);
+        config.interval = 2;
+        config
+    }
+
+    #[test]
+    fn test_islands() {
+        assert_eq!(Topology::Ring.sources(0, 3), [2]);
+        assert_eq!(Topology::Ring.sources(0, 1), [] as [usize; 0]);
+        assert_eq!(Topology::FullyConnected.sources(1, 3), [0, 2]);
+
+        // each island runs under its own seed, and the whole run is fixed by them
+        let mut islands = Islands::new(small_config()).unwrap();
+        assert_ne!(islands.islands[0].population, islands.islands[1].population);
+        let first = islands.run().unwrap();
+        assert_eq!(first.len(), 3);
+        assert!(first.iter().all(|reports| reports.len() == 5));
+        assert_eq!(islands.generation(), 5);
+        assert_eq!(first, Islands::new(small_config()).unwrap().run().unwrap());
+
+        // the islands share the CPUs, unless told otherwise
+        let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+        assert_eq!(islands.islands[0].config.threads, Some((cpus / 3).max(1)));
+        let mut config = small_config();
+        config.islands[1].threads = Some(5);
+        let threads: Vec<_> = Islands::new(config)
+            .unwrap()
+            .islands
+            .iter()
+            .map(|i| i.config.threads)
+            .collect();
+        assert_eq!(threads[1], Some(5));
+
+        // and checkpoint and log apart
+        let mut config = small_config().islands.remove(0);
+        config.checkpoint = Some("runs".into());
+        config.statistics = Some("logs/statistics.csv".into());
+        config.lineage = Some("logs/lineage".into());
+        let island = &IslandsConfig::new(config, 3).islands[2];
+        assert_eq!(island.checkpoint, Some("runs/island_2".into()));
+        assert_eq!(
+            island.statistics,
+            Some("logs/statistics_island_2.csv".into())
+        );
+        assert_eq!(island.lineage, Some("logs/lineage_island_2".into()));
+
+        // migrants replace the last warriors, never the elite
+        let dwarf = Warrior::load("src/test/warriors/dwarf.red", 800).unwrap();
+        let imp = Warrior::load("src/test/warriors/imp.red", 800).unwrap();
+        let mut islands = Islands::new(small_config()).unwrap();
+        let before: Vec<_> = islands
+            .islands
+            .iter()
+            .map(|i| i.population.clone())
+            .collect();
+
+        islands.migrate(&[Some(dwarf.clone()), None, Some(imp.clone())]);
+        assert_eq!(islands.islands[0].population[3], imp);
+        assert_eq!(islands.islands[1].population[3], dwarf);
+        assert_eq!(islands.islands[2].population, before[2]);
+        assert_eq!(islands.islands[0].population[..3], before[0][..3]);
+
+        // lineage ids are unique across islands, migrants are born again
+        let ids: HashSet<u64> = islands
+            .islands
+            .iter()
+            .flat_map(|i| i.population.iter().map(|w| w.lineage.id))
+            .collect();
+        assert_eq!(ids.len(), 3 * 4);
+        let migrant = &islands.islands[1].population[3].lineage;
+        assert_eq!(migrant.id >> ISLAND_ID_BITS, 1);
+        assert_eq!(migrant.parents, [dwarf.lineage.id]);
+        assert_eq!(migrant.operators, ["migration"]);
+
+        islands.config.topology = Topology::FullyConnected;
+        islands.migrate(&[Some(dwarf.clone()), Some(dwarf.clone()), Some(imp.clone())]);
+        assert_eq!(islands.islands[1].population[2..], [imp, dwarf.clone()]);
+        assert_eq!(islands.islands[1].population[0], before[1][0]);
+    }
+}
@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{
+        battle::BattleConfig,
+        evolution::{read_lineage, Evolution, EvolutionConfig},
+        warrior::Lineage,
+    };
+
+    #[test]
+    fn test_lineage() {
+        let record = Lineage {
+            id: 7,
+            generation: 2,
+            parents: vec![3, 5],
+            operators: vec!["uniform".into(), "tweak_value".into()],
+        };
+        assert_eq!(record.to_csv(), "7,2,3 5,uniform tweak_value");
+        assert_eq!(Lineage::parse_csv(&record.to_csv()), Ok(record.clone()));
+        let source = format!("{}ORG 0\nDAT #0, #0\n", record.to_comments());
+        assert_eq!(Lineage::from_comments(&source), Some(record.clone()));
+        assert!(Lineage::parse_csv("7,2").is_err());
+
+        // ancestors come nearest first, shared ones only once
+        let founder = |id| Lineage {
+            id,
+            ..Default::default()
+        };
+        let child = Lineage {
+            id: 5,
+            generation: 1,
+            parents: vec![1],
+            operators: vec![],
+        };
+        let records = [founder(1), founder(3), child, record];
+        let ids: Vec<u64> = Lineage::family_tree(&records, 7)
+            .iter()
+            .map(|r| r.id)
+            .collect();
+        assert_eq!(ids, [7, 3, 5, 1]);
+    }
+
+    #[test]
+    fn test_generation_log() {
+        let dir = std::env::temp_dir().join(format!("core_war_vm_log_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut battle = BattleConfig::new(800);
+        battle.max_cycles = 800;
+        battle.rounds = 2;
+        let mut config = EvolutionConfig::new(battle);
+        config.population = 6;
+        config.generations = 3;
+        config.warrior_length = 4;
+        config.elitism = 1;
+        config.seed = Some(5);
+        config.statistics = Some(dir.join("stats.csv"));
+        config.lineage = Some(dir.join("lineage.csv"));
+
+        let mut evolution = Evolution::new(config.clone()).unwrap();
+        let reports = evolution.run().unwrap();
+
+        // every warrior is born once, after its parents
+        let records = read_lineage(&dir.join("lineage.csv")).unwrap();
+        assert_eq!(records.len(), 6 + 2 * 5);
+        for (i, record) in records.iter().enumerate() {
+            assert_eq!(record.id, i as u64);
+            if record.generation > 0 {
+                let parent = records[record.parents[0] as usize].generation;
+                assert!(parent < record.generation);
+            }
+        }
+
+        let last = reports.last().unwrap();
+        let tree = Lineage::family_tree(&records, last.best.lineage.id);
+        assert_eq!(tree.last().unwrap().generation, 0);
+
+        let report = &reports[0];
+        assert!(report.median_score <= report.best_score);
+        assert!(report.diversity > 0. && report.diversity <= 1.);
+        assert_eq!(report.mean_length, 4.);
+        assert_eq!(report.opcodes.iter().sum::<usize>(), 6 * 4);
+
+        let stats = fs::read_to_string(dir.join("stats.csv")).unwrap();
+        let lines: Vec<&str> = stats.lines().collect();
+        assert!(lines[0].starts_with("generation,best_score,"));
+        assert_eq!(lines.len(), 1 + reports.len());
+        assert_eq!(lines[1], report.to_csv());
+
+        // JSON lines instead, one object per generation
+        config.statistics = Some(dir.join("stats.jsonl"));
+        config.lineage = None;
+        Evolution::new(config).unwrap().run().unwrap();
+        let stats = fs::read_to_string(dir.join("stats.jsonl")).unwrap();
+        assert_eq!(stats.lines().count(), reports.len());
+        assert!(stats.starts_with("{\"generation\":0,"));
+        assert!(stats.lines().all(|l| l.contains("\"opcodes\":{\"DAT\":")));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
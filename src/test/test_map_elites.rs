@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        battle::BattleConfig,
+        core::{stats::WarriorStats, CoreConfig},
+        evolution::EvolutionConfig,
+        map_elites::{Axis, Descriptor, MapElites, MapElitesConfig},
+        utils::ModUsize,
+        warrior::Warrior,
+    };
+
+    #[test]
+    fn test_warrior_stats() {
+        let dwarf = Warrior::load("src/test/warriors/dwarf.red", 800).unwrap();
+        let spl_imp = Warrior::load("src/test/warriors/spl_imp.red", 800).unwrap();
+
+        let mut conf = CoreConfig::new(800);
+        conf.deploy(dwarf.clone(), Some(ModUsize::new(0, 800)))
+            .unwrap();
+        conf.deploy(spl_imp, Some(ModUsize::new(400, 800))).unwrap();
+
+        let mut runtime = conf.brawl();
+        assert!(runtime.stats().is_none());
+        runtime.collect_stats(true);
+        conf.brawl_into(&mut runtime);
+
+        for _ in 0..12 {
+            runtime.tick();
+        }
+
+        // ADD, MOV, JMP, ADD, MOV, JMP: the ADDs hit the same cell, the MOVs two bombs
+        let stats = runtime.stats().unwrap();
+        assert_eq!(
+            stats[0],
+            WarriorStats {
+                rounds: 1,
+                cycles: 6,
+                spawned: 0,
+                writes: 4,
+                cells_written: 3,
+                away: 0,
+            }
+        );
+
+        // the imps run ahead of the two cells the warrior was loaded into
+        assert_eq!(stats[1].cycles, 6);
+        assert!(stats[1].spawned > 0);
+        assert!(stats[1].away > 0);
+
+        // statistics are per round
+        conf.brawl_into(&mut runtime);
+        assert_eq!(runtime.stats().unwrap()[0].cycles, 0);
+
+        // a division writes only the fields whose divisor isn't zero
+        for (source, writes) in [("DIV.AB #0, 1", 0), ("MOD.F #0, #2", 1)] {
+            let warrior = Warrior::parse(source.into(), "div".into(), 800).unwrap();
+            let mut conf = CoreConfig::new(800);
+            conf.deploy(warrior, None).unwrap();
+            runtime.collect_stats(true);
+            conf.brawl_into(&mut runtime);
+            runtime.tick();
+            assert_eq!(runtime.stats().unwrap()[0].writes, writes, "{source}");
+        }
+
+        let axis = Axis {
+            descriptor: Descriptor::Movement,
+            min: 0.,
+            max: 1.,
+            bins: 4,
+        };
+        assert_eq!([-1., 0.5, 0.99, 5.].map(|v| axis.bin(v)), [0, 2, 3, 3]);
+
+        let stats = WarriorStats {
+            rounds: 2,
+            cycles: 10,
+            spawned: 6,
+            writes: 5,
+            cells_written: 400,
+            away: 1,
+        };
+        let measures = Descriptor::ALL.map(|d| d.measure(&dwarf, &stats, 800));
+        assert_eq!(measures, [3., 0.25, 4., 0.5, 0.1]);
+    }
+
+    #[test]
+    fn test_map_elites() {
+        let mut battle = BattleConfig::new(800);
+        battle.max_cycles = 800;
+        battle.rounds = 2;
+
+        let mut evolution = EvolutionConfig::new(battle);
+        evolution.population = 6;
+        evolution.warrior_length = 5;
+        evolution.seed = Some(2);
+
+        let mut config = MapElitesConfig::new(evolution);
+        config
+            .axes
+            .push(Axis::new(Descriptor::Length, &config.evolution.battle));
+        config.iterations = 4;
+
+        let mut map_elites = MapElites::new(config.clone());
+        let reports = map_elites.run().unwrap();
+        assert_eq!(reports.len(), 4);
+        assert!(reports.windows(2).all(|r| r[0].filled <= r[1].filled));
+        assert_eq!(reports[3].filled, map_elites.archive.len());
+
+        // every elite sits in the cell its behavior maps to
+        for (cell, elite) in map_elites.archive.iter() {
+            let bins: Vec<usize> = config
+                .axes
+                .iter()
+                .zip(&elite.descriptors)
+                .map(|(axis, value)| axis.bin(*value))
+                .collect();
+            assert_eq!(*cell, bins);
+            assert_eq!(elite.descriptors[2], elite.warrior.body.len() as f64);
+        }
+
+        let best = map_elites.best().unwrap().fitness;
+        assert_eq!(reports[3].best_fitness, best);
+
+        // a seed fixes the whole run
+        let mut again = MapElites::new(config);
+        again.run().unwrap();
+        assert_eq!(again.archive, map_elites.archive);
+    }
+}
@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        batch::Batch, battle::BattleConfig, benchmark::Benchmark, minimizer::Minimizer,
+        warrior::Warrior,
+    };
+
+    #[test]
+    fn test_minimizer() {
+        let mut battle = BattleConfig::new(800);
+        battle.max_cycles = 800;
+        let mut batch = Batch::new(battle);
+        batch.seed = Some(2);
+
+        let imp = Warrior::load("src/test/warriors/imp.red", 800).unwrap();
+        let mut benchmark = Benchmark::new(vec![imp]);
+        benchmark.rounds = 10;
+
+        // a dwarf behind code that's never run
+        let source = ";name Padded dwarf
+            SPL.B #3, >7
+            MUL.AB *2, {5
+            ORG 2
+            ADD #4, 3
+            MOV 2, @2
+            JMP -2, 0
+            DAT #0, #0
+            SNE.X }4, $1";
+        let padded = Warrior::parse(source.into(), "".into(), 800).unwrap();
+        assert_eq!(padded.org, 2);
+
+        let mut minimizer = Minimizer::new(benchmark, batch);
+        minimizer.config.tolerance = 0.;
+        let minimized = minimizer.minimize(&padded).unwrap();
+
+        // the padding goes, and the dwarf bombs with its own MOV instead
+        assert!(minimized.score >= minimized.original_score);
+        assert_eq!(minimized.warrior.body.len(), 3);
+        assert_eq!(minimized.warrior.org, 0);
+        assert_eq!(minimized.warrior.body[0], padded.body[2]);
+        assert!(minimized.evaluations > 1);
+
+        // with every edit disabled there's nothing to try
+        minimizer.config.remove = false;
+        minimizer.config.replace_with_dat = false;
+        minimizer.config.simplify_fields = false;
+        let untouched = minimizer.minimize(&padded).unwrap();
+        assert_eq!(untouched.warrior, padded);
+        assert_eq!(untouched.evaluations, 1);
+    }
+}
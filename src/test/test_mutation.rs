@@ -0,0 +1,111 @@
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        battle::BattleConfig,
+        mutation::{MutationConfig, MutationOperator},
+        warrior::Warrior,
+    };
+
+    fn only(operator: MutationOperator) -> MutationConfig {
+        let mut config = MutationConfig::new(&BattleConfig::new(800));
+        for op in MutationOperator::ALL {
+            let weight = if op == operator { 1. } else { 0. };
+            match op {
+                MutationOperator::TweakValue => config.tweak_value = weight,
+                MutationOperator::ChangeMode => config.change_mode = weight,
+                MutationOperator::ChangeOpcode => config.change_opcode = weight,
+                MutationOperator::ChangeModifier => config.change_modifier = weight,
+                MutationOperator::Insert => config.insert = weight,
+                MutationOperator::Delete => config.delete = weight,
+                MutationOperator::Duplicate => config.duplicate = weight,
+                MutationOperator::Swap => config.swap = weight,
+                MutationOperator::ShiftOrg => config.shift_org = weight,
+                MutationOperator::Replace => config.replace = weight,
+                MutationOperator::UseConstant => config.use_constant = weight,
+            }
+        }
+        config.constants = vec![17];
+        config
+    }
+
+    #[test]
+    fn test_mutation() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let dwarf = Warrior::load("src/test/warriors/dwarf.red", 800).unwrap();
+
+        for operator in MutationOperator::ALL {
+            let config = only(operator);
+
+            for _ in 0..50 {
+                let (offspring, applied) = config.mutate(&dwarf, &mut rng).unwrap();
+                assert_eq!(applied, operator);
+                assert!(offspring.org < offspring.body.len());
+
+                let changed: Vec<usize> = (0..4)
+                    .filter(|i| offspring.body.get(*i) != dwarf.body.get(*i))
+                    .collect();
+
+                match operator {
+                    MutationOperator::TweakValue => {
+                        assert_eq!(changed.len(), 1);
+                        let (a, b) = (offspring.body[changed[0]], dwarf.body[changed[0]]);
+                        assert_eq!((a.code, a.modifier), (b.code, b.modifier));
+                        let delta = |i: usize| {
+                            let d = a.fields[i].get_val().val as isize
+                                - b.fields[i].get_val().val as isize;
+                            d.rem_euclid(800).min((-d).rem_euclid(800))
+                        };
+                        assert!(delta(0) + delta(1) <= config.max_delta as isize);
+                    }
+                    MutationOperator::ChangeMode => {
+                        assert_eq!(changed.len(), 1);
+                        let (a, b) = (offspring.body[changed[0]], dwarf.body[changed[0]]);
+                        assert_eq!(a.code, b.code);
+                        for i in 0..2 {
+                            assert_eq!(a.fields[i].get_val(), b.fields[i].get_val());
+                        }
+                    }
+                    MutationOperator::ChangeOpcode | MutationOperator::ChangeModifier => {
+                        assert_eq!(changed.len(), 1);
+                        assert_eq!(
+                            offspring.body[changed[0]].fields,
+                            dwarf.body[changed[0]].fields
+                        );
+                    }
+                    MutationOperator::Insert | MutationOperator::Duplicate => {
+                        assert_eq!(offspring.body.len(), 5);
+                        assert_eq!(offspring.body[offspring.org], dwarf.body[dwarf.org]);
+                    }
+                    MutationOperator::Delete => assert_eq!(offspring.body.len(), 3),
+                    MutationOperator::Swap => {
+                        assert_eq!(changed.len(), 2);
+                        assert_eq!(offspring.body[changed[0]], dwarf.body[changed[1]]);
+                    }
+                    MutationOperator::ShiftOrg => {
+                        assert_eq!(offspring.body, dwarf.body);
+                        assert_ne!(offspring.org, dwarf.org);
+                    }
+                    MutationOperator::Replace => assert!(changed.len() <= 1),
+                    MutationOperator::UseConstant => {
+                        assert_eq!(changed.len(), 1);
+                        let fields = offspring.body[changed[0]].fields;
+                        assert!(fields.iter().any(|f| f.get_val().val == 17));
+                    }
+                }
+            }
+        }
+
+        // nothing to do: growing past the length limit or deleting the last instruction
+        let mut config = only(MutationOperator::Insert);
+        config.max_length = 4;
+        assert!(config.mutate(&dwarf, &mut rng).is_none());
+
+        let mut single = dwarf.clone();
+        single.body.truncate(1);
+        assert!(only(MutationOperator::Delete)
+            .mutate(&single, &mut rng)
+            .is_none());
+    }
+}
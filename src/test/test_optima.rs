@@ -0,0 +1,42 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        optima::{optima, Pattern},
+        warrior::Warrior,
+    };
+
+    #[test]
+    fn test_optima() {
+        // the classic mod-4 dwarf step for 8000 cells
+        let pattern = Pattern::new(8000, 3044);
+        assert_eq!(pattern.modulus, 4);
+        assert_eq!(pattern.fill_order.len(), 1999);
+        assert_eq!(pattern.stages.last().unwrap().largest_gap, 4);
+        assert_eq!(pattern.bombs_to_cover(4), Some(1999));
+        assert_eq!(pattern.cycles_to_cover(4, 3), Some(3 * 1999));
+        assert_eq!(pattern.bombs_to_cover(3), None);
+        for (k, cell) in pattern.fill_order.iter().enumerate() {
+            assert_eq!(*cell, (k + 1) * 3044 % 8000);
+        }
+
+        // stepping 1 at a time only ever shrinks the gap ahead
+        let linear = Pattern::new(80, 1);
+        assert_eq!(linear.stages.len(), 79);
+        assert_eq!(linear.stages[0].largest_gap, 79);
+        assert_eq!(linear.stages[0].smallest_gap, 1);
+
+        // the best steps hit everything soonest, and more evenly than stepping 1
+        let best = optima(80, 4);
+        let fewest = (1..=40)
+            .filter_map(|step| Pattern::new(80, step).bombs_to_cover(4))
+            .min();
+        assert_eq!(best[0].bombs_to_cover(4), fewest);
+        assert!(best.iter().all(|p| p.modulus <= 4 && p.step <= 40));
+        assert!(best[0].unevenness(4) < linear.unevenness(4));
+
+        let equ = best[0].to_equ("step");
+        let warrior = Warrior::parse(format!("{equ}\nDAT #step, #-step"), "".into(), 80);
+        let warrior = warrior.unwrap();
+        assert_eq!(warrior.body[0].fields[0].get_val().val, best[0].step);
+    }
+}
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        batch::Batch,
+        battle::BattleConfig,
+        benchmark::Benchmark,
+        optimizer::{instantiate, Optimizer, Parameter, Search},
+        warrior::Warrior,
+    };
+
+    const DWARF: &str = ";name Dwarf
+                ORG start
+        step    EQU 4      ;opt step 1..20
+        start   ADD #step, bomb
+                MOV bomb, @bomb
+                JMP start
+        bomb    DAT #0, #0";
+
+    #[test]
+    fn test_optimizer() {
+        let parameters = Parameter::parse_all(DWARF).unwrap();
+        assert_eq!(
+            parameters,
+            [Parameter {
+                name: "step".into(),
+                min: 1,
+                max: 20,
+                initial: 4,
+            }]
+        );
+        let source = instantiate(DWARF, &parameters, &[7]);
+        assert!(source.contains("step EQU 7 ;opt step 1..20"));
+        let warrior = Warrior::parse(source, "".into(), 800).unwrap();
+        assert_eq!(warrior.body[0].to_string(), "ADD #7, 3");
+        assert!(Parameter::parse_all(";opt gap 1..5").is_err());
+
+        let mut battle = BattleConfig::new(800);
+        battle.max_cycles = 2000;
+        let mut batch = Batch::new(battle);
+        batch.seed = Some(6);
+        let imp = Warrior::load("src/test/warriors/imp.red", 800).unwrap();
+        let mut benchmark = Benchmark::new(vec![imp]);
+        benchmark.rounds = 4;
+        let mut optimizer = Optimizer::new(benchmark, batch);
+        optimizer.seed = Some(1);
+
+        // every value once, best first
+        optimizer.search = Search::Grid(50);
+        let grid = optimizer.optimize(DWARF).unwrap();
+        assert_eq!(grid.trials.len(), 20);
+        assert!(grid.trials.windows(2).all(|t| t[0].score >= t[1].score));
+        let best = grid.best().unwrap().values[0];
+        assert!(grid.source.contains(&format!("step EQU {best} ")));
+        assert_eq!(grid.to_string().lines().count(), 21);
+
+        optimizer.search = Search::Grid(3);
+        let values: Vec<isize> = optimizer
+            .optimize(DWARF)
+            .unwrap()
+            .trials
+            .iter()
+            .map(|t| t.values[0])
+            .collect();
+        assert_eq!(values.len(), 3);
+        assert!([1, 10, 20].iter().all(|v| values.contains(v)));
+
+        // climbing never ends below where it started
+        optimizer.search = Search::HillClimb(10);
+        let climb = optimizer.optimize(DWARF).unwrap();
+        let start = climb.trials.iter().find(|t| t.values == [4]).unwrap();
+        assert!(climb.best().unwrap().score >= start.score);
+        assert!(climb.trials.len() <= 11);
+
+        optimizer.search = Search::Random(5);
+        assert!(optimizer.optimize(DWARF).unwrap().trials.len() <= 5);
+        optimizer.search = Search::Random(0);
+        assert!(optimizer.optimize(DWARF).is_err());
+    }
+}
@@ -0,0 +1,111 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        core::CoreConfig, test::differential::harness::run_differential, utils::ModUsize,
+        warrior::Warrior,
+    };
+
+    fn queue(runtime: &crate::core::CoreRuntime) -> Vec<usize> {
+        runtime.warriors[0]
+            .get_counters()
+            .iter()
+            .map(|ic| ic.val)
+            .collect()
+    }
+
+    #[test]
+    fn test_process_queue() {
+        const CORE_SIZE: usize = 8000;
+        let mut core_conf = CoreConfig::new(CORE_SIZE);
+
+        let code = match Warrior::parse(
+            "spl 2
+jmp 0
+spl 0
+dat 0"
+                .into(),
+            "code".into(),
+            CORE_SIZE,
+        ) {
+            Ok(res) => res,
+            Err(err) => panic!("el parsing del warrior a fallado: {}", err),
+        };
+
+        core_conf
+            .deploy(code, Some(ModUsize::new(0, CORE_SIZE)))
+            .unwrap();
+        core_conf.set_max_processes(3);
+
+        let mut runtime = core_conf.brawl();
+
+        // the successor is queued before the SPL child
+        runtime.tick();
+        assert_eq!(queue(&runtime), vec![1, 2]);
+
+        runtime.tick();
+        assert_eq!(queue(&runtime), vec![2, 1]);
+
+        runtime.tick();
+        assert_eq!(queue(&runtime), vec![1, 3, 2]);
+
+        runtime.tick();
+        assert_eq!(queue(&runtime), vec![3, 2, 1]);
+
+        // the DAT kills the executing process, not the tail of the queue
+        runtime.tick();
+        assert_eq!(queue(&runtime), vec![2, 1]);
+
+        runtime.tick();
+        assert_eq!(queue(&runtime), vec![1, 3, 2]);
+
+        // with the queue full SPL behaves like NOP
+        core_conf.set_max_processes(2);
+
+        let mut runtime = core_conf.brawl();
+
+        runtime.tick();
+        runtime.tick();
+        runtime.tick();
+        assert_eq!(queue(&runtime), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_split_heavy_queues() {
+        // worked out by hand: three SPL 1 in a row leave eight processes, all
+        // on the line after them, the textbook way of starting an imp spiral
+        let spiral = Warrior::parse(
+            "spl 1\nspl 1\nspl 1\nmov 0, 1".into(),
+            "spiral".into(),
+            8000,
+        )
+        .unwrap();
+        let mut core_conf = CoreConfig::new(8000);
+        core_conf
+            .deploy(spiral, Some(ModUsize::new(0, 8000)))
+            .unwrap();
+        let mut runtime = core_conf.brawl();
+        let ticks = [vec![1, 1], vec![1, 2, 2], vec![2, 2, 2, 2]];
+        for expected in ticks {
+            runtime.tick();
+            assert_eq!(queue(&runtime), expected);
+        }
+        for _ in 0..4 {
+            runtime.tick();
+        }
+        assert_eq!(queue(&runtime), [3; 8]);
+
+        // silk papers, and a vampire whose pit fills the queue up to the
+        // limit. These traces come from the reference model in
+        // test::reference, not from pMARS, so they only show the runtime
+        // and the model agree
+        for trace in [
+            "src/test/traces/paper_vs_vampire.trace",
+            "src/test/traces/silk_vs_dwarf.trace",
+            "src/test/traces/vampire_vs_dwarf.trace",
+        ] {
+            if let Err(report) = run_differential(trace) {
+                panic!("{report}");
+            }
+        }
+    }
+}
@@ -0,0 +1,79 @@
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use serde::{de::DeserializeOwned, Serialize};
+
+    use crate::{
+        battle::{run_match, BattleConfig},
+        core::CoreConfig,
+        evolution::{Evolution, EvolutionConfig},
+        utils::ModUsize,
+        warrior::Lineage,
+        warrior::Warrior,
+    };
+
+    fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+        let json = serde_json::to_string(value).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_serde() {
+        let mut dwarf = Warrior::load("src/test/warriors/dwarf.red", 800).unwrap();
+        dwarf.lineage = Lineage {
+            id: 12,
+            generation: 3,
+            parents: vec![4, 9],
+            operators: vec!["splice".into(), "flip_mode".into()],
+        };
+        let imp = Warrior::load("src/test/warriors/imp.red", 800).unwrap();
+        let copy = round_trip(&dwarf);
+        assert_eq!((&copy, &copy.lineage), (&dwarf, &dwarf.lineage));
+
+        // a snapshot taken mid battle plays on exactly like the original
+        let mut config = CoreConfig::new(800);
+        config
+            .deploy(dwarf.clone(), Some(ModUsize::new(0, 800)))
+            .unwrap();
+        config
+            .deploy(imp.clone(), Some(ModUsize::new(400, 800)))
+            .unwrap();
+        let mut runtime = config.brawl();
+        runtime.collect_stats(true);
+        round_trip(&config).brawl_into(&mut runtime);
+        for _ in 0..300 {
+            runtime.tick();
+        }
+        let mut snapshot = round_trip(&runtime);
+        for _ in 0..3000 {
+            runtime.tick();
+            snapshot.tick();
+        }
+        assert_eq!(snapshot.core, runtime.core);
+        assert_eq!(snapshot.warriors, runtime.warriors);
+        assert!(runtime.stats().is_some());
+        assert_eq!(snapshot.stats(), runtime.stats());
+
+        let mut battle = BattleConfig::new(800);
+        battle.rounds = 4;
+        let result = run_match(&[dwarf, imp], &battle).unwrap();
+        assert_eq!(round_trip(&battle), battle);
+        assert_eq!(round_trip(&result), result);
+
+        // and so does an evolution, random generator included
+        let mut battle = BattleConfig::new(800);
+        battle.max_cycles = 800;
+        battle.rounds = 2;
+        let mut config = EvolutionConfig::new(battle);
+        config.population = 6;
+        config.warrior_length = 5;
+        config.seed = Some(3);
+        let mut evolution = Evolution::new(config).unwrap();
+        evolution.step().unwrap();
+        let mut resumed = round_trip(&evolution);
+        assert_eq!(resumed.config, evolution.config);
+        for _ in 0..2 {
+            assert_eq!(resumed.step().unwrap(), evolution.step().unwrap());
+        }
+        assert_eq!(resumed.population, evolution.population);
+    }
+}
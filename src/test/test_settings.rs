@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use crate::settings::parse;
+
+    #[test]
+    fn test_settings() {
+        let table = parse(
+            r#"
+            name = "a # not a comment \"quoted\"" # a comment
+            count = 1_000
+            rate = -0.5
+            on = true
+            steps = [1, 2,
+                     3]   # arrays can go on over lines
+
+            [battle]
+            core_size = 8000
+
+            [hill.rules]
+            standard = "94nop"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            table.string("name"),
+            Ok(Some("a # not a comment \"quoted\""))
+        );
+        assert_eq!(table.usize("count"), Ok(Some(1000)));
+        assert_eq!(table.float("rate"), Ok(Some(-0.5)));
+        assert_eq!(table.float("count"), Ok(Some(1000.)));
+        assert_eq!(table.boolean("on"), Ok(Some(true)));
+        let steps = table.array("steps").unwrap().unwrap();
+        let steps: Vec<_> = steps.iter().map(|v| v.as_integer()).collect();
+        assert_eq!(steps, [Some(1), Some(2), Some(3)]);
+        assert_eq!(table.usize("missing"), Ok(None));
+
+        let battle = table.table("battle").unwrap().unwrap();
+        assert_eq!(battle.usize("core_size"), Ok(Some(8000)));
+        let rules = table.table("hill").unwrap().unwrap().table("rules");
+        assert_eq!(
+            rules.unwrap().unwrap().string("standard"),
+            Ok(Some("94nop"))
+        );
+        let inline = parse("battle = { rounds = 4 }").unwrap();
+        let battle = inline.table("battle").unwrap().unwrap();
+        assert_eq!(battle.usize("rounds"), Ok(Some(4)));
+
+        // errors name the key
+        assert_eq!(
+            table.usize("rate"),
+            Err("rate should be an integer, not a float".into())
+        );
+        assert_eq!(
+            battle.expect_only(&["max_cycles"]),
+            Err("unknown key battle.rounds".into())
+        );
+        assert_eq!(
+            table.usize("name"),
+            Err("name should be an integer, not a string".into())
+        );
+        assert!(parse("a = 1\na = 2").is_err());
+        // a table given twice, and a quoted key that only looks dotted
+        assert!(parse("[battle]\nrounds = 1\n[battle]\nrounds = 2").is_err());
+        let quoted = parse("\"battle.rounds\" = 1").unwrap();
+        assert_eq!(
+            quoted.expect_only(&["battle"]),
+            Err("unknown key battle.rounds".into())
+        );
+        assert!(quoted.table("battle").unwrap().is_none());
+        assert!(parse("a = \"open").is_err());
+        assert!(parse("a = [1, 2").is_err());
+        assert!(parse("[table").is_err());
+        assert!(parse("a = what").is_err());
+    }
+}
@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+    use crate::{battle::BattleConfig, tournament::Tournament};
+
+    #[test]
+    fn test_tournament() {
+        let mut config = BattleConfig::new(800);
+        config.rounds = 4;
+
+        let tournament = Tournament::load_dir("src/test/warriors", config).unwrap();
+        let result = tournament.run().unwrap();
+
+        assert_eq!(result.names, vec!["Dwarf", "Imp", "Spl imp", "Suicide"]);
+
+        // the suicide dies on its first instruction, every round
+        let last = result.standings.last().unwrap();
+        assert_eq!(last.name, "Suicide");
+        assert_eq!((last.wins, last.losses, last.ties), (0, 12, 0));
+        assert_eq!(last.score, 0.);
+        for i in 0..3 {
+            assert_eq!(result.matrix[i][3], 12.);
+        }
+
+        for standing in result.standings.iter() {
+            assert_eq!(standing.wins + standing.losses + standing.ties, 12);
+        }
+
+        let text = result.to_text();
+        assert!(text.starts_with("Rank"));
+        assert!(text.contains("Suicide"));
+
+        let csv = result.to_csv();
+        assert!(csv.starts_with("rank,name,wins,losses,ties,score,percentage\n"));
+        assert_eq!(csv.lines().count(), 1 + 4 + 1 + 1 + 4);
+
+        let json = result.to_json();
+        assert!(json.starts_with("{\"standings\":[{\"name\":"));
+        assert!(json.contains("\"names\":[\"Dwarf\",\"Imp\",\"Spl imp\",\"Suicide\"]"));
+    }
+
+    #[test]
+    fn test_melee() {
+        let mut config = BattleConfig::new(800);
+        config.rounds = 2;
+
+        let mut tournament = Tournament::load_dir("src/test/warriors", config).unwrap();
+        tournament.melee_size = 3;
+        let result = tournament.run().unwrap();
+
+        // four warriors make four different melees of three
+        for standing in result.standings.iter() {
+            assert_eq!(standing.wins + standing.losses + standing.ties, 3 * 2);
+        }
+        assert_eq!(result.standings.last().unwrap().name, "Suicide");
+    }
+}
@@ -0,0 +1,305 @@
+use std::{fmt::Write, fs, thread};
+
+use crate::{batch::Batch, battle::BattleConfig, warrior::Warrior};
+
+pub struct Tournament {
+    pub config: BattleConfig,
+    pub warriors: Vec<Warrior>,
+    /// Warriors per battle: 2 plays every pairing, more plays every melee of
+    /// that many warriors.
+    pub melee_size: usize,
+    pub threads: usize,
+    /// Makes the placements, and so the results, reproducible.
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Standing {
+    pub name: String,
+    pub wins: usize,
+    pub losses: usize,
+    pub ties: usize,
+    pub score: f64,
+    pub percentage: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TournamentResult {
+    pub names: Vec<String>,
+    /// `matrix[i][j]` is the score `i` earned in the battles `j` was part of.
+    pub matrix: Vec<Vec<f64>>,
+    /// Sorted from best to worst score.
+    pub standings: Vec<Standing>,
+}
+
+impl Tournament {
+    pub fn new(warriors: Vec<Warrior>, config: BattleConfig) -> Self {
+        Self {
+            config,
+            warriors,
+            melee_size: 2,
+            threads: thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            seed: None,
+        }
+    }
+
+    /// Builds a tournament out of every `.red` file in `dir`.
+    pub fn load_dir(dir: &str, config: BattleConfig) -> Result<Self, String> {
+        let mut paths: Vec<String> = fs::read_dir(dir)
+            .map_err(|err| format!("reading {dir} failed: {err}"))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "red"))
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+
+        paths.sort();
+
+        let warriors = paths
+            .iter()
+            .map(|path| Warrior::load(path, config.core_size))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self::new(warriors, config))
+    }
+
+    fn battles(&self) -> Vec<Vec<usize>> {
+        let mut battles = vec![];
+        let mut current = vec![];
+        combinations(
+            self.warriors.len(),
+            self.melee_size,
+            0,
+            &mut current,
+            &mut battles,
+        );
+        battles
+    }
+
+    pub fn run(&self) -> Result<TournamentResult, String> {
+        if self.melee_size < 2 || self.melee_size > self.warriors.len() {
+            return Err(format!(
+                "can't hold battles of {} out of {} warriors",
+                self.melee_size,
+                self.warriors.len()
+            ));
+        }
+
+        let battles = self.battles();
+        let batch = Batch {
+            config: self.config.clone(),
+            threads: self.threads,
+            seed: self.seed,
+            stats: false,
+        };
+        let results = batch.run(&self.warriors, &battles)?;
+
+        let n = self.warriors.len();
+        let mut matrix = vec![vec![0.; n]; n];
+        let mut standings: Vec<Standing> = self
+            .warriors
+            .iter()
+            .map(|w| Standing {
+                name: w.name.clone(),
+                wins: 0,
+                losses: 0,
+                ties: 0,
+                score: 0.,
+                percentage: 0.,
+            })
+            .collect();
+        let mut rounds_played = vec![0; n];
+
+        for (battle, result) in battles.iter().zip(results) {
+            for (slot, w) in battle.iter().enumerate() {
+                standings[*w].wins += result.wins[slot];
+                standings[*w].losses += result.losses[slot];
+                standings[*w].ties += result.ties[slot];
+                standings[*w].score += result.scores[slot];
+                rounds_played[*w] += self.config.rounds;
+
+                for opponent in battle.iter().filter(|o| *o != w) {
+                    matrix[*w][*opponent] += result.scores[slot];
+                }
+            }
+        }
+
+        let best_round = self.config.round_points(self.melee_size, 1);
+        for (standing, rounds) in standings.iter_mut().zip(rounds_played) {
+            if rounds > 0 {
+                standing.percentage = standing.score * 100. / (best_round * rounds as f64);
+            }
+        }
+
+        standings.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+        Ok(TournamentResult {
+            names: self.warriors.iter().map(|w| w.name.clone()).collect(),
+            matrix,
+            standings,
+        })
+    }
+}
+
+fn combinations(
+    n: usize,
+    k: usize,
+    start: usize,
+    current: &mut Vec<usize>,
+    out: &mut Vec<Vec<usize>>,
+) {
+    if current.len() == k {
+        out.push(current.clone());
+        return;
+    }
+
+    for i in start..n {
+        current.push(i);
+        combinations(n, k, i + 1, current, out);
+        current.pop();
+    }
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut ret = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(ret, "\\u{:04x}", c as u32);
+            }
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+impl TournamentResult {
+    pub fn to_text(&self) -> String {
+        let width = self.names.iter().map(|n| n.len()).max().unwrap_or(0).max(4);
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            "{:>4}  {:<width$}  {:>6} {:>6} {:>6} {:>10} {:>7}",
+            "Rank", "Name", "W", "L", "T", "Score", "%"
+        );
+        for (rank, s) in self.standings.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "{:>4}  {:<width$}  {:>6} {:>6} {:>6} {:>10.2} {:>7.2}",
+                rank + 1,
+                s.name,
+                s.wins,
+                s.losses,
+                s.ties,
+                s.score,
+                s.percentage
+            );
+        }
+
+        let _ = writeln!(out);
+        let _ = write!(out, "{:<width$}", "");
+        for i in 0..self.names.len() {
+            let _ = write!(out, " {:>8}", i + 1);
+        }
+        let _ = writeln!(out);
+        for (i, row) in self.matrix.iter().enumerate() {
+            let _ = write!(out, "{:<width$}", self.names[i]);
+            for (j, score) in row.iter().enumerate() {
+                if i == j {
+                    let _ = write!(out, " {:>8}", "-");
+                } else {
+                    let _ = write!(out, " {:>8.2}", score);
+                }
+            }
+            let _ = writeln!(out);
+        }
+
+        out
+    }
+
+    /// The ranking table followed by the score matrix, separated by a blank
+    /// line.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("rank,name,wins,losses,ties,score,percentage\n");
+
+        for (rank, s) in self.standings.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{},{},{}",
+                rank + 1,
+                csv_field(&s.name),
+                s.wins,
+                s.losses,
+                s.ties,
+                s.score,
+                s.percentage
+            );
+        }
+
+        out.push('\n');
+        out.push_str("name");
+        for name in self.names.iter() {
+            let _ = write!(out, ",{}", csv_field(name));
+        }
+        out.push('\n');
+        for (name, row) in self.names.iter().zip(self.matrix.iter()) {
+            out.push_str(&csv_field(name));
+            for score in row {
+                let _ = write!(out, ",{score}");
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+
+    pub fn to_json(&self) -> String {
+        let standings: Vec<String> = self
+            .standings
+            .iter()
+            .map(|s| {
+                format!(
+                    "{{\"name\":{},\"wins\":{},\"losses\":{},\"ties\":{},\"score\":{},\"percentage\":{}}}",
+                    json_string(&s.name),
+                    s.wins,
+                    s.losses,
+                    s.ties,
+                    s.score,
+                    s.percentage
+                )
+            })
+            .collect();
+        let names: Vec<String> = self.names.iter().map(|n| json_string(n)).collect();
+        let matrix: Vec<String> = self
+            .matrix
+            .iter()
+            .map(|row| {
+                let row: Vec<String> = row.iter().map(|s| s.to_string()).collect();
+                format!("[{}]", row.join(","))
+            })
+            .collect();
+
+        format!(
+            "{{\"standings\":[{}],\"names\":[{}],\"matrix\":[{}]}}",
+            standings.join(","),
+            names.join(","),
+            matrix.join(",")
+        )
+    }
+}
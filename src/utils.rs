@@ -11,6 +11,7 @@ pub fn modulo<T: TryInto<isize>, Q: TryInto<isize>>(dividend: T, divisor: Q) ->
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ModUsize {
     pub val: usize,
     pub congruence: usize,
@@ -32,8 +33,14 @@ impl Add for ModUsize {
     type Output = ModUsize;
 
     fn add(self, rhs: Self) -> Self::Output {
+        // both values are already reduced, so one subtraction is enough
+        let val = self.val + rhs.val;
         Self {
-            val: modulo(self.val + rhs.val, self.congruence),
+            val: if val >= self.congruence {
+                val - self.congruence
+            } else {
+                val
+            },
             congruence: self.congruence,
         }
     }
@@ -43,10 +50,7 @@ impl Add<usize> for ModUsize {
     type Output = ModUsize;
 
     fn add(self, rhs: usize) -> Self::Output {
-        Self {
-            val: modulo(self.val + rhs, self.congruence),
-            congruence: self.congruence,
-        }
+        self + ModUsize::reduce(rhs, self.congruence)
     }
 }
 
@@ -66,7 +70,11 @@ impl Sub for ModUsize {
 
     fn sub(self, rhs: ModUsize) -> Self::Output {
         Self {
-            val: modulo(self.val as isize - rhs.val as isize, self.congruence),
+            val: if self.val >= rhs.val {
+                self.val - rhs.val
+            } else {
+                self.val + self.congruence - rhs.val
+            },
             congruence: self.congruence,
         }
     }
@@ -76,10 +84,7 @@ impl Sub<usize> for ModUsize {
     type Output = ModUsize;
 
     fn sub(self, rhs: usize) -> Self::Output {
-        Self {
-            val: modulo(self.val as isize - rhs as isize, self.congruence),
-            congruence: self.congruence,
-        }
+        self - ModUsize::reduce(rhs, self.congruence)
     }
 }
 
@@ -168,16 +173,29 @@ impl ModUsize {
         }
     }
 
-    pub fn inc(&self, x: isize) -> ModUsize {
+    // skips the division for the small steps the runtime takes most of the time
+    fn reduce(val: usize, congruence: usize) -> ModUsize {
         Self {
-            val: modulo(self.val + modulo(x, self.congruence), self.congruence),
-            congruence: self.congruence,
+            val: if val < congruence {
+                val
+            } else {
+                val % congruence
+            },
+            congruence,
+        }
+    }
+
+    pub fn inc(&self, x: isize) -> ModUsize {
+        if x >= 0 {
+            *self + x as usize
+        } else {
+            *self - x.unsigned_abs()
         }
     }
 
-    pub(crate) fn rand(congruence: usize, range: Range<usize>) -> ModUsize {
+    pub(crate) fn rand(congruence: usize, range: Range<usize>, rng: &mut impl Rng) -> ModUsize {
         Self {
-            val: rand::thread_rng().gen_range(range),
+            val: rng.gen_range(range),
             congruence: congruence,
         }
     }
@@ -1,39 +1,163 @@
+use std::{collections::VecDeque, fs, path::Path};
+
+use rand::prelude::SliceRandom;
 use rand::Rng;
 
-use crate::{instruction::instruction::Instruction, utils::ModUsize};
-    use rand::prelude::SliceRandom;
+use crate::{assembler::assemble, instruction::instruction::Instruction, utils::ModUsize};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Warrior {
     pub org: usize,
     pub name: String,
     pub body: Vec<Instruction>,
-    instruction_counters: Vec<ModUsize>,
+    instruction_counters: VecDeque<ModUsize>,
+    pub(crate) deploy_index: usize,
+    pub lineage: Lineage,
+}
+
+/// Where an evolved warrior came from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lineage {
+    /// Unique within an evolution run.
+    pub id: u64,
+    /// Generation the warrior was born in.
+    pub generation: usize,
+    pub parents: Vec<u64>,
+    /// Crossover and mutation operators applied, in order.
+    pub operators: Vec<String>,
+}
+
+impl Lineage {
+    pub const CSV_HEADER: &'static str = "id,generation,parents,operators";
+
+    /// One CSV row, lists separated by spaces.
+    pub fn to_csv(&self) -> String {
+        let parents: Vec<String> = self.parents.iter().map(|p| p.to_string()).collect();
+        format!(
+            "{},{},{},{}",
+            self.id,
+            self.generation,
+            parents.join(" "),
+            self.operators.join(" ")
+        )
+    }
+
+    pub fn parse_csv(line: &str) -> Result<Self, String> {
+        let bad = || format!("malformed lineage \"{line}\"");
+        let fields: Vec<&str> = line.split(',').collect();
+        let [id, generation, parents, operators] = fields[..] else {
+            return Err(bad());
+        };
+
+        Ok(Self {
+            id: id.parse().map_err(|_| bad())?,
+            generation: generation.parse().map_err(|_| bad())?,
+            parents: parents
+                .split_whitespace()
+                .map(|p| p.parse().map_err(|_| bad()))
+                .collect::<Result<_, _>>()?,
+            operators: operators.split_whitespace().map(String::from).collect(),
+        })
+    }
+
+    /// The same, as `;` comments to put in redcode.
+    pub fn to_comments(&self) -> String {
+        let row = self.to_csv();
+        format!(";lineage {row}\n")
+    }
+
+    /// Reads back what `to_comments` wrote, if `source` has it.
+    pub fn from_comments(source: &str) -> Option<Self> {
+        source
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(";lineage "))
+            .and_then(|row| Self::parse_csv(row.trim()).ok())
+    }
+
+    /// The record of `id` followed by those of all its ancestors found in
+    /// `records`, nearest first.
+    pub fn family_tree(records: &[Lineage], id: u64) -> Vec<Lineage> {
+        let find = |id: u64| records.iter().find(|r| r.id == id);
+
+        let mut tree: Vec<Lineage> = find(id).into_iter().cloned().collect();
+        let mut i = 0;
+        while i < tree.len() {
+            for parent in tree[i].parents.clone() {
+                if tree.iter().all(|r| r.id != parent) {
+                    tree.extend(find(parent).cloned());
+                }
+            }
+            i += 1;
+        }
+
+        tree
+    }
+}
+
+// the lineage tells where a warrior came from, not what it is
+impl PartialEq for Warrior {
+    fn eq(&self, other: &Self) -> bool {
+        self.org == other.org
+            && self.name == other.name
+            && self.body == other.body
+            && self.instruction_counters == other.instruction_counters
+            && self.deploy_index == other.deploy_index
+    }
 }
 
 impl Warrior {
+    /// A nameless warrior with no body, only there to hold a queue, see
+    /// `CoreRuntime::load_with_rng`.
+    pub(crate) fn process_holder() -> Self {
+        Self {
+            org: 0,
+            name: String::new(),
+            body: vec![],
+            instruction_counters: VecDeque::new(),
+            deploy_index: 0,
+            lineage: Lineage::default(),
+        }
+    }
+
+    /// Empties the queue for a new round, leaving a single process at `ptr`.
+    pub(crate) fn restart(&mut self, deploy_index: usize, ptr: ModUsize) {
+        self.deploy_index = deploy_index;
+        self.instruction_counters.clear();
+        self.instruction_counters.push_back(ptr);
+    }
+
     pub fn new_thread(&mut self, ptr: ModUsize) {
-        self.instruction_counters.push(ptr);
+        self.instruction_counters.push_back(ptr);
     }
 
     pub fn get_counters(&self) -> Vec<ModUsize> {
-        self.instruction_counters.clone()
+        self.instruction_counters.iter().copied().collect()
+    }
+
+    pub fn thread_count(&self) -> usize {
+        self.instruction_counters.len()
     }
 
     pub fn random_create(size: usize, core_size: usize) -> Self {
+        Self::random_create_with_rng(size, core_size, &mut rand::thread_rng())
+    }
+
+    pub fn random_create_with_rng(size: usize, core_size: usize, rng: &mut impl Rng) -> Self {
         let mut body = vec![];
 
         for _ in 0..size {
             // println!("creating random instruction");
 
-            let inst = Instruction::get_random(size, core_size);
+            let inst = Instruction::get_random_with_rng(size, core_size, rng);
 
             // println!("{inst:?}");
 
             body.push(inst)
         }
 
-        let org = rand::thread_rng().gen_range(0..size);
+        let org = rng.gen_range(0..size);
 
         // println!("\n\norg:{org}\n\n");
 
@@ -41,52 +165,78 @@ impl Warrior {
             org,
             name: "random".into(),
             body,
-            instruction_counters: vec![],
+            instruction_counters: VecDeque::new(),
+            deploy_index: 0,
+            lineage: Lineage::default(),
         }
     }
 
-    pub fn get_next_instruction_counter(&mut self) -> ModUsize {
-        let ret = self.instruction_counters[0];
-        self.instruction_counters.rotate_left(1);
-        ret
-    }
-
-    pub fn set_last_instruction_counter(&mut self, val: ModUsize) {
-        let last_i = self.instruction_counters.len() - 1;
-        self.instruction_counters[last_i] = val;
+    /// Removes the process at the head of the queue, the caller is responsible
+    /// for queueing its successor (and any SPL child) once it has executed.
+    pub fn pop_instruction_counter(&mut self) -> Option<ModUsize> {
+        self.instruction_counters.pop_front()
     }
 
     pub fn parse(str: String, name: String, core_size: usize) -> Result<Self, String> {
         let str = str.to_uppercase();
 
+        let assembled = assemble(&str)?;
+
         let mut body = vec![];
-        let mut start = None;
-
-        for (i, line) in str.split('\n').enumerate() {
-            let line = line.trim();
-            if line.starts_with("ORG") {
-                if let None = start {
-                    start = Some(i);
-                } else {
-                    return Err(format!("linea {i}: multiple ORG pseudoinstructions found"));
-                }
-            } else {
-                match Instruction::parse(line.into(), core_size) {
-                    Ok(None) => (),
-                    Ok(Some(op)) => body.push(op),
-                    Err(err) => return Err(format!("linea {i}: {err}")),
-                }
+        for (i, line) in assembled.lines {
+            match Instruction::parse(line, core_size) {
+                Ok(None) => (),
+                Ok(Some(op)) => body.push(op),
+                Err(err) => return Err(format!("linea {i}: {err}")),
             }
         }
 
         Ok(Self {
-            org: start.unwrap_or_else(|| 0),
+            org: assembled.org.unwrap_or(0),
             name,
-            instruction_counters: vec![],
+            instruction_counters: VecDeque::new(),
+            deploy_index: 0,
+            lineage: Lineage::default(),
             body,
         })
     }
 
+    /// Reads a warrior from a `.red` file, naming it after its `;name`
+    /// comment or, failing that, after the file.
+    pub fn load(file_path: &str, core_size: usize) -> Result<Self, String> {
+        let source = fs::read_to_string(file_path)
+            .map_err(|err| format!("reading warrior {file_path} failed: {err}"))?;
+
+        let name = Self::declared_name(&source).unwrap_or_else(|| {
+            Path::new(file_path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| file_path.to_string())
+        });
+
+        Self::parse(source, name, core_size)
+    }
+
+    /// The warrior as a load file: every instruction fully spelled out and
+    /// the start given by an ORG.
+    pub fn to_load_file(&self) -> String {
+        let mut source = format!(";redcode-94\n;name {}\nORG {}\n", self.name, self.org);
+        for instruction in self.body.iter() {
+            source.push_str(&format!("{instruction}\n"));
+        }
+        source.push_str("END\n");
+        source
+    }
+
+    /// The name given by a `;name` comment in redcode source, if any.
+    pub fn declared_name(source: &str) -> Option<String> {
+        source
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(";name"))
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+    }
+
     #[cfg(not(tarpaulin_include))]
     pub(crate) fn print_state_at(&self, line: usize) {
         for (thread_i, ic) in self.instruction_counters.iter().enumerate() {
@@ -96,20 +246,22 @@ impl Warrior {
         }
     }
 
-    pub(crate) fn kill_thread(&mut self) {
-        let last_i = self.instruction_counters.len() - 1;
-        self.instruction_counters.remove(last_i);
-    }
-
     pub(crate) fn dead(&self) -> bool {
-        self.instruction_counters.len() == 0
+        self.instruction_counters.is_empty()
     }
 
+    pub fn mutate(&self) -> Warrior {
+        self.mutate_with_rng(&mut rand::thread_rng())
+    }
 
-    pub(crate) fn mutate(&self) -> Warrior {
+    /// A copy with one instruction mutated, or just a copy if there's
+    /// no instruction to mutate.
+    pub fn mutate_with_rng(&self, rng: &mut impl Rng) -> Warrior {
         let mut offspring = self.clone();
 
-        offspring.body.choose_mut(&mut rand::thread_rng()).unwrap().mutate();
+        if let Some(instruction) = offspring.body.choose_mut(rng) {
+            instruction.mutate(rng);
+        }
 
         offspring
     }